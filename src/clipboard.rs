@@ -4,12 +4,23 @@
 
 use crate::TextError;
 use dyn_clone::DynClone;
+use std::cmp::min;
+use std::collections::VecDeque;
 use std::error::Error;
 use std::fmt::{Debug, Display, Formatter};
 use std::sync::{Arc, Mutex};
 
+/// Clipboard errors.
+///
+/// More variants may be added in the future.
 #[derive(Debug)]
-pub struct ClipboardError;
+#[non_exhaustive]
+pub enum ClipboardError {
+    /// Accessing the clipboard failed.
+    Failed,
+    /// The clipboard doesn't support this operation.
+    Unsupported,
+}
 
 impl Display for ClipboardError {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
@@ -32,13 +43,41 @@ pub trait Clipboard: DynClone + Debug {
 
     /// Set text from the clipboard.
     fn set_string(&self, s: &str) -> Result<(), ClipboardError>;
+
+    /// Get html from the clipboard.
+    ///
+    /// The default returns [ClipboardError::Unsupported].
+    fn get_html(&self) -> Result<String, ClipboardError> {
+        Err(ClipboardError::Unsupported)
+    }
+
+    /// Set html for the clipboard. This is set in addition to
+    /// [Clipboard::set_string()], and must be called after it.
+    /// A clipboard may drop the html when a new string is set.
+    ///
+    /// The default returns [ClipboardError::Unsupported].
+    fn set_html(&self, _html: &str) -> Result<(), ClipboardError> {
+        Err(ClipboardError::Unsupported)
+    }
+
+    /// Rotate to the next older entry of a clipboard that keeps
+    /// more than one.
+    ///
+    /// The default returns [ClipboardError::Unsupported].
+    fn cycle(&self) -> Result<(), ClipboardError> {
+        Err(ClipboardError::Unsupported)
+    }
 }
 
 /// Local clipboard.
 /// A string in disguise.
+///
+/// Setting a string removes the html, so the html of an older copy
+/// can't be pasted with the new text.
 #[derive(Debug, Default, Clone)]
 pub struct LocalClipboard {
     text: Arc<Mutex<String>>,
+    html: Arc<Mutex<String>>,
 }
 
 impl LocalClipboard {
@@ -51,17 +90,130 @@ impl Clipboard for LocalClipboard {
     fn get_string(&self) -> Result<String, ClipboardError> {
         match self.text.lock() {
             Ok(v) => Ok(v.clone()),
-            Err(_) => Err(ClipboardError),
+            Err(_) => Err(ClipboardError::Failed),
         }
     }
 
+    /// Sets the text and clears the html. Call [Clipboard::set_html()]
+    /// afterwards to keep html for the new text.
     fn set_string(&self, s: &str) -> Result<(), ClipboardError> {
+        match self.html.lock() {
+            Ok(mut v) => v.clear(),
+            Err(_) => return Err(ClipboardError::Failed),
+        }
         match self.text.lock() {
             Ok(mut v) => {
                 *v = s.to_string();
                 Ok(())
             }
-            Err(_) => Err(ClipboardError),
+            Err(_) => Err(ClipboardError::Failed),
+        }
+    }
+
+    fn get_html(&self) -> Result<String, ClipboardError> {
+        match self.html.lock() {
+            Ok(v) => Ok(v.clone()),
+            Err(_) => Err(ClipboardError::Failed),
+        }
+    }
+
+    fn set_html(&self, html: &str) -> Result<(), ClipboardError> {
+        match self.html.lock() {
+            Ok(mut v) => {
+                *v = html.to_string();
+                Ok(())
+            }
+            Err(_) => Err(ClipboardError::Failed),
+        }
+    }
+}
+
+/// Local clipboard that keeps the last N copied strings.
+///
+/// A small kill-ring. [Clipboard::get_string()] returns the
+/// current entry, [Clipboard::cycle()] rotates to the next older one.
+/// Empty strings are not stored.
+#[derive(Debug, Clone)]
+pub struct RingClipboard {
+    capacity: usize,
+    ring: Arc<Mutex<VecDeque<String>>>,
+}
+
+impl RingClipboard {
+    /// Keeps at most capacity entries.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            ring: Default::default(),
+        }
+    }
+
+    /// Number of stored entries.
+    pub fn len(&self) -> usize {
+        match self.ring.lock() {
+            Ok(v) => v.len(),
+            Err(_) => 0,
+        }
+    }
+
+    /// No entries.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl Clipboard for RingClipboard {
+    fn get_string(&self) -> Result<String, ClipboardError> {
+        match self.ring.lock() {
+            Ok(v) => Ok(v.front().cloned().unwrap_or_default()),
+            Err(_) => Err(ClipboardError::Failed),
+        }
+    }
+
+    /// Adds a new entry and drops the oldest, if the ring is full.
+    fn set_string(&self, s: &str) -> Result<(), ClipboardError> {
+        if s.is_empty() {
+            return Ok(());
+        }
+        match self.ring.lock() {
+            Ok(mut v) => {
+                v.push_front(s.to_string());
+                v.truncate(self.capacity);
+                Ok(())
+            }
+            Err(_) => Err(ClipboardError::Failed),
+        }
+    }
+
+    fn cycle(&self) -> Result<(), ClipboardError> {
+        match self.ring.lock() {
+            Ok(mut v) => {
+                v.rotate_left(min(1, v.len()));
+                Ok(())
+            }
+            Err(_) => Err(ClipboardError::Failed),
         }
     }
 }
+
+/// Clipboard that does nothing.
+/// Copy succeeds without storing anything, paste returns an
+/// empty string.
+#[derive(Debug, Default, Clone)]
+pub struct NullClipboard;
+
+impl NullClipboard {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Clipboard for NullClipboard {
+    fn get_string(&self) -> Result<String, ClipboardError> {
+        Ok(String::new())
+    }
+
+    fn set_string(&self, _s: &str) -> Result<(), ClipboardError> {
+        Ok(())
+    }
+}
@@ -19,6 +19,7 @@ use ratatui::prelude::{StatefulWidget, Style};
 use ratatui::widgets::Block;
 #[cfg(feature = "unstable-widget-ref")]
 use ratatui::widgets::StatefulWidgetRef;
+use std::borrow::Cow;
 use std::fmt;
 use std::ops::Range;
 use unicode_segmentation::UnicodeSegmentation;
@@ -532,7 +533,7 @@ impl DateInputState {
 
     /// Selection
     #[inline]
-    pub fn selected_text(&self) -> &str {
+    pub fn selected_text(&self) -> Cow<'_, str> {
         self.widget.selected_text()
     }
 }
@@ -461,6 +461,8 @@ pub(crate) struct GlyphIter<Iter> {
 
     tabs: u16,
     show_ctrl: bool,
+    tab_glyph: char,
+    space_glyph: Option<char>,
     line_break: bool,
 }
 
@@ -478,6 +480,8 @@ where
             screen_pos: Default::default(),
             tabs: 8,
             show_ctrl: false,
+            tab_glyph: '\u{2409}',
+            space_glyph: None,
             line_break: true,
         }
     }
@@ -506,6 +510,16 @@ where
     pub(crate) fn set_show_ctrl(&mut self, show_ctrl: bool) {
         self.show_ctrl = show_ctrl;
     }
+
+    /// Glyph for tabs if show_ctrl is active.
+    pub(crate) fn set_tab_glyph(&mut self, tab_glyph: char) {
+        self.tab_glyph = tab_glyph;
+    }
+
+    /// Glyph for spaces if show_ctrl is active.
+    pub(crate) fn set_space_glyph(&mut self, space_glyph: Option<char>) {
+        self.space_glyph = space_glyph;
+    }
 }
 
 impl<'a, Iter> Iterator for GlyphIter<Iter>
@@ -535,7 +549,15 @@ where
                 }
                 "\t" => {
                     len = self.tabs - (self.screen_pos.0 % self.tabs);
-                    glyph = Cow::Borrowed(if self.show_ctrl { "\u{2409}" } else { " " });
+                    glyph = if self.show_ctrl {
+                        Cow::Owned(self.tab_glyph.to_string())
+                    } else {
+                        Cow::Borrowed(" ")
+                    };
+                }
+                " " if self.show_ctrl && self.space_glyph.is_some() => {
+                    len = 1;
+                    glyph = Cow::Owned(self.space_glyph.expect("space_glyph").to_string());
                 }
                 c if ("\x00".."\x20").contains(&c) => {
                     static CCHAR: [&str; 32] = [
@@ -569,33 +591,35 @@ where
                 self.pos.x = 0;
                 self.pos.y += 1;
             } else {
-                self.screen_pos.0 += len;
+                // saturates for very long lines.
+                self.screen_pos.0 = self.screen_pos.0.saturating_add(len);
                 self.pos.x += 1;
             }
+            let screen_end = screen_pos.0.saturating_add(len);
 
             // clip left
             if screen_pos.0 < self.screen_offset {
-                if screen_pos.0 + len > self.screen_offset {
+                if screen_end > self.screen_offset {
                     // don't show partial glyphs, but show the space they need.
                     // avoids flickering when scrolling left/right.
                     return Some(Glyph {
                         glyph: Cow::Borrowed("\u{2203}"),
                         text_bytes: grapheme.text_bytes,
-                        screen_width: screen_pos.0 + len - self.screen_offset,
+                        screen_width: screen_end - self.screen_offset,
                         pos,
                         screen_pos: (0, screen_pos.1),
                     });
                 } else {
                     // out left
                 }
-            } else if screen_pos.0 + len > self.screen_offset + self.screen_width {
-                if screen_pos.0 < self.screen_offset + self.screen_width {
+            } else if screen_end > self.screen_offset.saturating_add(self.screen_width) {
+                if screen_pos.0 < self.screen_offset.saturating_add(self.screen_width) {
                     // don't show partial glyphs, but show the space they need.
                     // avoids flickering when scrolling left/right.
                     return Some(Glyph {
                         glyph: Cow::Borrowed("\u{2203}"),
                         text_bytes: grapheme.text_bytes,
-                        screen_width: screen_pos.0 + len - (self.screen_offset + self.screen_width),
+                        screen_width: screen_end - (self.screen_offset + self.screen_width),
                         pos,
                         screen_pos: (screen_pos.0 - self.screen_offset, screen_pos.1),
                     });
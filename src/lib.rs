@@ -58,6 +58,23 @@ pub mod event {
         TextChanged,
     }
 
+    impl TextOutcome {
+        /// Outcome of an edit.
+        ///
+        /// Returns TextChanged if the text changed, Changed if only
+        /// the cursor moved or the view scrolled and Unchanged otherwise.
+        /// Navigation and scrolling alone should never report TextChanged.
+        pub fn edit(text_changed: bool, changed: bool) -> Self {
+            if text_changed {
+                TextOutcome::TextChanged
+            } else if changed {
+                TextOutcome::Changed
+            } else {
+                TextOutcome::Unchanged
+            }
+        }
+    }
+
     impl ConsumedEvent for TextOutcome {
         fn is_consumed(&self) -> bool {
             *self != TextOutcome::Continue
@@ -125,6 +142,34 @@ impl Default for TextStyle {
     }
 }
 
+/// Statistics for the text.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct TextStats {
+    /// Number of graphemes. Line-breaks are not counted.
+    pub graphemes: usize,
+    /// Number of chars.
+    pub chars: usize,
+    /// Number of bytes.
+    pub bytes: usize,
+    /// Number of words. A word is everything that is not whitespace.
+    pub words: usize,
+    /// Number of lines.
+    pub lines: upos_type,
+}
+
+/// Suggested shape of the terminal cursor.
+///
+/// The widgets don't set the terminal cursor themselves, this is
+/// only a hint for applications that do.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum CaretShape {
+    /// Thin bar between two graphemes. Used when inserting.
+    #[default]
+    Bar,
+    /// Block over the current grapheme. Used when overwriting.
+    Block,
+}
+
 pub mod core {
     //!
     //! Core structs for text-editing.
@@ -208,6 +253,8 @@ pub enum TextError {
         usize, // Start.
         usize, // End.
     ),
+    /// The replay entry with the given index doesn't match the text.
+    ReplayConflict(usize),
 }
 
 impl Display for TextError {
@@ -227,6 +274,7 @@ pub type ipos_type = i32;
 
 /// Text position.
 #[derive(Default, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TextPosition {
     pub y: upos_type,
     pub x: upos_type,
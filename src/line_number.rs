@@ -5,12 +5,15 @@
 use crate::_private::NonExhaustive;
 use crate::upos_type;
 use format_num_pattern::NumberFormat;
+use rat_event::ct_event;
 use rat_event::util::MouseFlags;
 use ratatui::buffer::Buffer;
 use ratatui::layout::Rect;
 use ratatui::prelude::{BlockExt, StatefulWidget, Style};
 use ratatui::text::Line;
 use ratatui::widgets::{Block, Widget};
+use std::cmp::{max, min};
+use std::ops::Range;
 
 /// Renders line-numbers.
 ///
@@ -19,11 +22,14 @@ use ratatui::widgets::{Block, Widget};
 /// [`LineNumberState`] to handle common actions.
 #[derive(Debug, Default, Clone)]
 pub struct LineNumbers<'a> {
-    start: upos_type,
+    start: Option<upos_type>,
     end: Option<upos_type>,
     cursor: upos_type,
     relative: bool,
+    row_lines: Vec<upos_type>,
+    continuation: Option<char>,
     flags: Vec<Line<'a>>,
+    flag_columns: Vec<(u16, Vec<Line<'a>>)>,
 
     flag_width: Option<u16>,
     margin: (u16, u16),
@@ -54,10 +60,22 @@ pub struct LineNumberState {
     pub area: Rect,
     pub inner: Rect,
 
+    /// First line rendered. This is the start set at the widget
+    /// or the offset.
+    /// __read only__ renewed with each render.
     pub start: upos_type,
+    /// First line, used if no start is set at the widget.
+    /// __read+write__
+    pub offset: upos_type,
+    /// Logical line for each rendered row, if the widget got
+    /// [LineNumbers::row_lines].
+    /// __read only__ renewed with each render.
+    pub row_lines: Vec<upos_type>,
 
     /// Helper for mouse.
     pub mouse: MouseFlags,
+    /// Start line of a mouse drag.
+    pub drag_start: Option<upos_type>,
 
     pub non_exhaustive: NonExhaustive,
 }
@@ -68,8 +86,11 @@ impl<'a> LineNumbers<'a> {
     }
 
     /// Start position.
+    ///
+    /// This overrides [LineNumberState::offset], if neither is
+    /// set the numbers start at 0.
     pub fn start(mut self, start: upos_type) -> Self {
-        self.start = start;
+        self.start = Some(start);
         self
     }
 
@@ -91,7 +112,30 @@ impl<'a> LineNumbers<'a> {
         self
     }
 
+    /// Logical line for each screen row.
+    ///
+    /// Use this if the text is soft-wrapped. A row with the same
+    /// line as the row before is a continuation and is rendered
+    /// without a number. Rows without an entry stay empty.
+    ///
+    /// If this is set, start and [LineNumberState::offset] are
+    /// not used for numbering.
+    pub fn row_lines(mut self, row_lines: Vec<upos_type>) -> Self {
+        self.row_lines = row_lines;
+        self
+    }
+
+    /// Marker for continuation rows, right aligned with the numbers.
+    /// The default leaves them blank.
+    pub fn continuation_marker(mut self, marker: char) -> Self {
+        self.continuation = Some(marker);
+        self
+    }
+
     /// Extra info.
+    ///
+    /// This is the first flag column. More can be added with
+    /// [LineNumbers::flag_column].
     pub fn flags(mut self, flags: Vec<Line<'a>>) -> Self {
         self.flags = flags;
         self
@@ -103,6 +147,37 @@ impl<'a> LineNumbers<'a> {
         self
     }
 
+    /// Add another column of flags with the given width.
+    /// The columns are rendered after [LineNumbers::flags]
+    /// separated by a single space.
+    pub fn flag_column(mut self, width: u16, flags: Vec<Line<'a>>) -> Self {
+        self.flag_columns.push((width, flags));
+        self
+    }
+
+    /// Width of the first flag column.
+    fn first_flag_width(&self) -> u16 {
+        if let Some(flag_width) = self.flag_width {
+            flag_width
+        } else {
+            self.flags
+                .iter()
+                .map(|v| v.width() as u16)
+                .max()
+                .unwrap_or_default()
+        }
+    }
+
+    /// Width of all flag columns.
+    fn flags_width(&self) -> u16 {
+        self.first_flag_width()
+            + self
+                .flag_columns
+                .iter()
+                .map(|(width, _)| width + 1)
+                .sum::<u16>()
+    }
+
     /// Extra margin.
     pub fn margin(mut self, margin: (u16, u16)) -> Self {
         self.margin = margin;
@@ -156,22 +231,55 @@ impl<'a> LineNumbers<'a> {
         self
     }
 
-    /// Calculates the necessary width for the configuration.
-    pub fn width(&self) -> u16 {
-        let nr_width = if let Some(end) = self.end {
-            end.ilog10() as u16 + 1
+    /// Width of the line-numbers.
+    ///
+    /// Uses end if it is set. Otherwise, this reserves space for
+    /// at least 100 lines after start, or the number of visible rows
+    /// if that is known and larger. This way [LineNumbers::width] and
+    /// render agree for every area up to 100 rows.
+    fn nr_width(&self, start: upos_type, rows: Option<u16>) -> u16 {
+        let max_nr = if let Some(end) = self.end {
+            end
         } else {
-            (self.start + 100).ilog10() as u16 + 1
+            start + max(100, rows.unwrap_or_default()) as upos_type
         };
-        let flag_width = if let Some(flag_width) = self.flag_width {
-            flag_width
+        max(max_nr, 1).ilog10() as u16 + 1
+    }
+
+    /// First line rendered. Uses the first of the row_lines, the start
+    /// or the offset, in that order.
+    fn start_line(&self, offset: upos_type) -> upos_type {
+        if let Some(first) = self.row_lines.first() {
+            *first
         } else {
-            self.flags
-                .iter()
-                .map(|v| v.width() as u16)
-                .max()
-                .unwrap_or_default()
-        };
+            self.start.unwrap_or(offset)
+        }
+    }
+
+    /// Calculates the necessary width for the configuration.
+    ///
+    /// This matches the rendered width for areas up to 100 rows.
+    /// Use [LineNumbers::width_for] if the area can be higher.
+    ///
+    /// The offset of the state is not known here, if the start
+    /// is not set this calculates with 0. Use [LineNumbers::width_for]
+    /// if the numbers follow [LineNumberState::offset].
+    pub fn width(&self) -> u16 {
+        self.calc_width(self.start_line(0), None)
+    }
+
+    /// Calculates the necessary width for rendering with the
+    /// given state and number of rows.
+    ///
+    /// This resolves the first line the same way render does,
+    /// so the layout matches the rendered gutter.
+    pub fn width_for(&self, state: &LineNumberState, rows: u16) -> u16 {
+        self.calc_width(self.start_line(state.offset), Some(rows))
+    }
+
+    fn calc_width(&self, start: upos_type, rows: Option<u16>) -> u16 {
+        let nr_width = self.nr_width(start, rows);
+        let flag_width = self.flags_width();
         let block_width = {
             let area = self.block.inner_if_some(Rect::new(0, 0, 2, 2));
             2 - area.width
@@ -201,24 +309,12 @@ impl<'a> StatefulWidget for LineNumbers<'a> {
     fn render(self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
         state.area = area;
         state.inner = self.block.inner_if_some(area);
-        state.start = self.start;
+        state.start = self.start_line(state.offset);
+        state.row_lines.clone_from(&self.row_lines);
         let end = self.end.unwrap_or(upos_type::MAX);
 
-        let nr_width = if let Some(end) = self.end {
-            end.ilog10() as u16 + 1
-        } else {
-            (self.start + 100).ilog10() as u16 + 1
-        };
-
-        let flag_width = if let Some(flag_width) = self.flag_width {
-            flag_width
-        } else {
-            self.flags
-                .iter()
-                .map(|v| v.width() as u16)
-                .max()
-                .unwrap_or_default()
-        };
+        let nr_width = self.nr_width(state.start, Some(state.inner.height));
+        let flag_width = self.first_flag_width();
 
         let format = if let Some(format) = self.format {
             format
@@ -238,16 +334,28 @@ impl<'a> StatefulWidget for LineNumbers<'a> {
 
         let mut tmp = String::new();
         for y in state.inner.top()..state.inner.bottom() {
-            let (nr, is_cursor) = if self.relative {
-                let pos = self.start + (y - state.inner.y) as upos_type;
-                (pos.abs_diff(self.cursor), pos == self.cursor)
+            let row = (y - state.inner.y) as usize;
+            let (pos, continued) = if self.row_lines.is_empty() {
+                (Some(state.start + row as upos_type), false)
             } else {
-                let pos = self.start + (y - state.inner.y) as upos_type;
-                (pos, pos == self.cursor)
+                let pos = self.row_lines.get(row).copied();
+                let continued =
+                    row > 0 && pos.is_some() && self.row_lines.get(row - 1) == pos.as_ref();
+                (pos, continued)
+            };
+            let (nr, is_cursor) = match pos {
+                Some(pos) if self.relative => (pos.abs_diff(self.cursor), pos == self.cursor),
+                Some(pos) => (pos, pos == self.cursor),
+                None => (end, false),
             };
 
             tmp.clear();
-            if nr < end {
+            if continued {
+                if let Some(marker) = self.continuation {
+                    tmp.push_str(&" ".repeat(nr_width.saturating_sub(1) as usize));
+                    tmp.push(marker);
+                }
+            } else if nr < end {
                 _ = format.fmt_to(nr, &mut tmp);
             }
 
@@ -262,16 +370,17 @@ impl<'a> StatefulWidget for LineNumbers<'a> {
             .intersection(area);
             buf.set_stringn(nr_area.x, nr_area.y, &tmp, nr_area.width as usize, style);
 
+            let mut flag_x = state.inner.x + self.margin.0 + nr_width + 1;
             if let Some(flags) = self.flags.get((y - state.inner.y) as usize) {
-                flags.render(
-                    Rect::new(
-                        state.inner.x + self.margin.0 + nr_width + 1,
-                        y,
-                        flag_width,
-                        1,
-                    ),
-                    buf,
-                );
+                flags.render(Rect::new(flag_x, y, flag_width, 1), buf);
+            }
+            flag_x += flag_width;
+            for (width, flags) in &self.flag_columns {
+                flag_x += 1;
+                if let Some(flags) = flags.get((y - state.inner.y) as usize) {
+                    flags.render(Rect::new(flag_x, y, *width, 1), buf);
+                }
+                flag_x += width;
             }
         }
     }
@@ -283,7 +392,10 @@ impl Default for LineNumberState {
             area: Default::default(),
             inner: Default::default(),
             start: 0,
+            offset: 0,
+            row_lines: Default::default(),
             mouse: Default::default(),
+            drag_start: None,
             non_exhaustive: NonExhaustive,
         }
     }
@@ -293,4 +405,58 @@ impl LineNumberState {
     pub fn new() -> Self {
         Self::default()
     }
+
+    /// First line, used if no start is set at the widget.
+    ///
+    /// This way the gutter can follow the scroll position kept
+    /// in the state. Use [LineNumbers::width_for] to get the
+    /// matching width for the layout.
+    #[inline]
+    pub fn set_offset(&mut self, offset: upos_type) {
+        self.offset = offset;
+    }
+
+    /// First line, used if no start is set at the widget.
+    #[inline]
+    pub fn offset(&self) -> upos_type {
+        self.offset
+    }
+
+    /// Logical line for a rendered row.
+    fn row_line(&self, row: u16) -> Option<upos_type> {
+        let row = row - self.inner.y;
+        if self.row_lines.is_empty() {
+            Some(self.start + row as upos_type)
+        } else {
+            self.row_lines.get(row as usize).copied()
+        }
+    }
+
+    /// Tracks a click and drag in the gutter and returns the
+    /// range of lines covered so far.
+    ///
+    /// The range is clamped to the rendered rows.
+    pub fn dragged_range(&mut self, event: &crossterm::event::Event) -> Option<Range<upos_type>> {
+        match event {
+            ct_event!(mouse any for m) if self.mouse.drag(self.inner, m) => {
+                let start = self.drag_start?;
+                let row = m
+                    .row
+                    .clamp(self.inner.top(), self.inner.bottom().saturating_sub(1));
+                let end = self.row_line(row).or(self.row_lines.last().copied())?;
+                Some(min(start, end)..max(start, end) + 1)
+            }
+            ct_event!(mouse down Left for column,row) => {
+                if self.inner.contains((*column, *row).into()) {
+                    self.drag_start = self.row_line(*row);
+                    let start = self.drag_start?;
+                    Some(start..start + 1)
+                } else {
+                    self.drag_start = None;
+                    None
+                }
+            }
+            _ => None,
+        }
+    }
 }
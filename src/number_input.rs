@@ -18,6 +18,7 @@ use ratatui::prelude::{StatefulWidget, Style};
 use ratatui::widgets::Block;
 #[cfg(feature = "unstable-widget-ref")]
 use ratatui::widgets::StatefulWidgetRef;
+use std::borrow::Cow;
 use std::fmt::{Debug, Display, LowerExp};
 use std::ops::Range;
 use std::str::FromStr;
@@ -427,7 +428,7 @@ impl NumberInputState {
 
     /// Selection
     #[inline]
-    pub fn selected_text(&self) -> &str {
+    pub fn selected_text(&self) -> Cow<'_, str> {
         self.widget.selected_text()
     }
 }
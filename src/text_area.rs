@@ -12,7 +12,8 @@ use crate::text_store::text_rope::TextRope;
 use crate::text_store::TextStore;
 use crate::undo_buffer::{UndoBuffer, UndoEntry, UndoVec};
 use crate::{
-    ipos_type, upos_type, Cursor, HasScreenCursor, TextError, TextPosition, TextRange, TextStyle,
+    ipos_type, upos_type, Cursor, HasScreenCursor, TextError, TextPosition, TextRange, TextStats,
+    TextStyle,
 };
 use crossterm::event::KeyModifiers;
 use rat_event::util::MouseFlags;
@@ -509,6 +510,29 @@ impl TextAreaState {
         self.value.glyph_ctrl()
     }
 
+    /// Glyph used for tabs if control characters are shown.
+    #[inline]
+    pub fn set_tab_glyph(&mut self, glyph: char) {
+        self.value.set_tab_glyph(glyph);
+    }
+
+    /// Glyph used for tabs if control characters are shown.
+    pub fn tab_glyph(&self) -> char {
+        self.value.tab_glyph()
+    }
+
+    /// Glyph used for spaces if control characters are shown.
+    /// None leaves spaces as they are.
+    #[inline]
+    pub fn set_space_glyph(&mut self, glyph: Option<char>) {
+        self.value.set_space_glyph(glyph);
+    }
+
+    /// Glyph used for spaces if control characters are shown.
+    pub fn space_glyph(&self) -> Option<char> {
+        self.value.space_glyph()
+    }
+
     /// Extra column information for cursor movement.
     ///
     /// The cursor position is capped to the current line length, so if you
@@ -551,6 +575,9 @@ impl TextAreaState {
         };
 
         _ = clip.set_string(self.selected_text().as_ref());
+        if let Ok(html) = self.value.str_slice_html(self.value.selection()) {
+            _ = clip.set_html(&html);
+        }
         false
     }
 
@@ -562,7 +589,12 @@ impl TextAreaState {
         };
 
         match clip.set_string(self.selected_text().as_ref()) {
-            Ok(_) => self.delete_range(self.selection()),
+            Ok(_) => {
+                if let Ok(html) = self.value.str_slice_html(self.value.selection()) {
+                    _ = clip.set_html(&html);
+                }
+                self.delete_range(self.selection())
+            }
             Err(_) => false,
         }
     }
@@ -628,6 +660,15 @@ impl TextAreaState {
         self.value.replay_log(replay)
     }
 
+    /// Apply the replay recording. Stops at the first entry that
+    /// doesn't match the current text.
+    ///
+    /// See [TextCore::replay_log_checked()]
+    #[inline]
+    pub fn replay_log_checked(&mut self, replay: &[UndoEntry]) -> Result<(), TextError> {
+        self.value.replay_log_checked(replay)
+    }
+
     /// Undo operation
     #[inline]
     pub fn undo(&mut self) -> bool {
@@ -732,6 +773,18 @@ impl TextAreaState {
         self.value.set_cursor(cursor.into(), extend_selection)
     }
 
+    /// Set the cursor position.
+    /// Returns an error if the position is outside the text,
+    /// [TextAreaState::set_cursor()] silently caps it.
+    #[inline]
+    pub fn try_set_cursor(
+        &mut self,
+        cursor: impl Into<TextPosition>,
+        extend_selection: bool,
+    ) -> Result<bool, TextError> {
+        self.value.try_set_cursor(cursor.into(), extend_selection)
+    }
+
     /// Selection anchor.
     #[inline]
     pub fn anchor(&self) -> TextPosition {
@@ -824,6 +877,12 @@ impl TextAreaState {
         self.value.len_lines()
     }
 
+    /// Statistics for the text.
+    #[inline]
+    pub fn stats(&self) -> TextStats {
+        self.value.stats()
+    }
+
     /// Line width as grapheme count.
     #[inline]
     pub fn line_width(&self, row: upos_type) -> upos_type {
@@ -1360,6 +1419,13 @@ impl TextAreaState {
         self.value.is_word_boundary(pos.into())
     }
 
+    /// Is the cursor at a word boundary?
+    pub fn cursor_at_word_boundary(&self) -> bool {
+        self.value
+            .is_word_boundary(self.value.cursor())
+            .expect("valid_cursor")
+    }
+
     /// Find the start of the word at pos.
     /// Returns pos if the position is not inside a word.
     pub fn word_start(&self, pos: impl Into<TextPosition>) -> TextPosition {
@@ -3,11 +3,23 @@ use crate::grapheme::{Glyph, GlyphIter, Grapheme};
 use crate::range_map::{expand_range_by, ranges_intersect, shrink_range_by, RangeMap};
 use crate::text_store::TextStore;
 use crate::undo_buffer::{StyleChange, TextPositionChange, UndoBuffer, UndoEntry, UndoOp};
-use crate::{upos_type, Cursor, TextError, TextPosition, TextRange};
+use crate::{upos_type, Cursor, TextError, TextPosition, TextRange, TextStats};
 use dyn_clone::clone_box;
 use std::borrow::Cow;
 use std::cmp::min;
 use std::ops::Range;
+use std::slice;
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Undo buffer and state taken out by [TextCore::suspend_undo].
+#[derive(Debug)]
+pub(crate) struct SuspendedUndo {
+    undo: Box<dyn UndoBuffer>,
+    text: String,
+    cursor: TextPosition,
+    anchor: TextPosition,
+    styles: Option<Vec<(Range<usize>, usize)>>,
+}
 
 /// Core for text editing.
 #[derive(Debug)]
@@ -35,6 +47,10 @@ pub struct TextCore<Store> {
     expand_tabs: bool,
     /// show ctrl chars in glyphs
     glyph_ctrl: bool,
+    /// glyph for tabs if glyph_ctrl is set
+    glyph_tab: char,
+    /// glyph for spaces if glyph_ctrl is set
+    glyph_space: Option<char>,
     /// use line-breaks in glyphs
     glyph_line_break: bool,
 }
@@ -52,6 +68,8 @@ impl<Store: Clone> Clone for TextCore<Store> {
             tabs: self.tabs,
             expand_tabs: self.expand_tabs,
             glyph_ctrl: self.glyph_ctrl,
+            glyph_tab: self.glyph_tab,
+            glyph_space: self.glyph_space,
             glyph_line_break: self.glyph_line_break,
         }
     }
@@ -70,6 +88,8 @@ impl<Store: TextStore + Default> TextCore<Store> {
             tabs: 8,
             expand_tabs: true,
             glyph_ctrl: false,
+            glyph_tab: '\u{2409}',
+            glyph_space: None,
             glyph_line_break: true,
         }
     }
@@ -126,6 +146,30 @@ impl<Store: TextStore + Default> TextCore<Store> {
         self.glyph_ctrl
     }
 
+    /// Glyph used for tabs if control characters are shown.
+    /// Default is '\u{2409}'.
+    #[inline]
+    pub fn set_tab_glyph(&mut self, glyph: char) {
+        self.glyph_tab = glyph;
+    }
+
+    /// Glyph used for tabs if control characters are shown.
+    pub fn tab_glyph(&self) -> char {
+        self.glyph_tab
+    }
+
+    /// Glyph used for spaces if control characters are shown.
+    /// Default is None, which leaves spaces as they are.
+    #[inline]
+    pub fn set_space_glyph(&mut self, glyph: Option<char>) {
+        self.glyph_space = glyph;
+    }
+
+    /// Glyph used for spaces if control characters are shown.
+    pub fn space_glyph(&self) -> Option<char> {
+        self.glyph_space
+    }
+
     /// Handle line-breaks when iterating glyphs.
     /// If false everything is treated as one line.
     #[inline]
@@ -169,6 +213,23 @@ impl<Store: TextStore + Default> TextCore<Store> {
         };
     }
 
+    /// Enable undo for setting/removing styles.
+    #[inline]
+    pub fn set_undo_styles(&mut self, undo_styles: bool) {
+        if let Some(undo) = self.undo.as_mut() {
+            undo.enable_undo_styles(undo_styles);
+        };
+    }
+
+    /// Undo for setting/removing styles.
+    pub fn undo_styles(&self) -> bool {
+        if let Some(undo) = self.undo.as_ref() {
+            undo.undo_styles_enabled()
+        } else {
+            false
+        }
+    }
+
     /// Begin a sequence of changes that should be undone in one go.
     #[inline]
     pub fn begin_undo_seq(&mut self) {
@@ -185,6 +246,133 @@ impl<Store: TextStore + Default> TextCore<Store> {
         };
     }
 
+    /// Run a batch of edits without recording each of them.
+    ///
+    /// Undo is disabled while f runs. Afterwards the net change is
+    /// recorded as a single undo step: the changed part of the text
+    /// is replaced, the cursor and anchor jump to their new position.
+    /// If style undo is enabled the styles are restored as a whole.
+    /// Intermediate states of the text can't be restored.
+    pub fn with_undo_suspended<R>(&mut self, f: impl FnOnce(&mut Self) -> R) -> R {
+        let suspended = self.suspend_undo();
+        let r = f(self);
+        self.resume_undo(suspended);
+        r
+    }
+
+    /// Take the undo buffer out and remember the current state.
+    /// Used to implement [TextCore::with_undo_suspended] for the widgets.
+    pub(crate) fn suspend_undo(&mut self) -> Option<SuspendedUndo> {
+        let undo = self.undo.take()?;
+        let styles = if undo.undo_styles_enabled() || undo.has_replay_log() {
+            self.styles.as_ref().map(|v| v.values().collect::<Vec<_>>())
+        } else {
+            None
+        };
+        Some(SuspendedUndo {
+            undo,
+            text: self.text.string(),
+            cursor: self.cursor,
+            anchor: self.anchor,
+            styles,
+        })
+    }
+
+    /// Put the undo buffer back and record the net change since
+    /// [TextCore::suspend_undo] as one undo step.
+    pub(crate) fn resume_undo(&mut self, suspended: Option<SuspendedUndo>) {
+        let Some(SuspendedUndo {
+            mut undo,
+            text: before,
+            cursor,
+            anchor,
+            styles,
+        }) = suspended
+        else {
+            return;
+        };
+        let after = self.text.string();
+
+        // trim the common parts by grapheme, so the recorded
+        // change never splits a grapheme.
+        let before_g = before.graphemes(true).collect::<Vec<_>>();
+        let after_g = after.graphemes(true).collect::<Vec<_>>();
+        let prefix_g = before_g
+            .iter()
+            .zip(after_g.iter())
+            .take_while(|(a, b)| a == b)
+            .count();
+        let prefix = before_g[..prefix_g].iter().map(|v| v.len()).sum::<usize>();
+        let suffix = before_g[prefix_g..]
+            .iter()
+            .rev()
+            .zip(after_g[prefix_g..].iter().rev())
+            .take_while(|(a, b)| a == b)
+            .map(|(a, _)| a.len())
+            .sum::<usize>();
+        let removed = &before[prefix..before.len() - suffix];
+        let inserted = &after[prefix..after.len() - suffix];
+
+        let cursor = TextPositionChange {
+            before: cursor,
+            after: self.cursor,
+        };
+        let anchor = TextPositionChange {
+            before: anchor,
+            after: self.anchor,
+        };
+        let styles = styles.and_then(|styles_before| {
+            let styles_after = self
+                .styles
+                .as_ref()
+                .map(|v| v.values().collect::<Vec<_>>())
+                .unwrap_or_default();
+            (styles_before != styles_after).then_some((styles_before, styles_after))
+        });
+
+        undo.begin_seq();
+        // undo runs the sequence backwards. the styles are cleared
+        // around the text changes and set at both ends.
+        if let Some((styles_before, _)) = &styles {
+            undo.append(UndoOp::SetStyles {
+                styles_before: styles_before.clone(),
+                styles_after: Vec::new(),
+            });
+        }
+        if !removed.is_empty() {
+            undo.append(UndoOp::RemoveStr {
+                bytes: prefix..prefix + removed.len(),
+                cursor: cursor.clone(),
+                anchor: anchor.clone(),
+                txt: removed.to_string(),
+                styles: Vec::new(),
+            });
+        }
+        if !inserted.is_empty() {
+            undo.append(UndoOp::InsertStr {
+                bytes: prefix..prefix + inserted.len(),
+                cursor: cursor.clone(),
+                anchor: anchor.clone(),
+                txt: inserted.to_string(),
+            });
+        }
+        if removed.is_empty()
+            && inserted.is_empty()
+            && (cursor.before != cursor.after || anchor.before != anchor.after)
+        {
+            undo.append(UndoOp::Cursor { cursor, anchor });
+        }
+        if let Some((_, styles_after)) = styles {
+            undo.append(UndoOp::SetStyles {
+                styles_before: Vec::new(),
+                styles_after,
+            });
+        }
+        undo.end_seq();
+
+        self.undo = Some(undo);
+    }
+
     /// Undo
     #[inline]
     pub fn undo_buffer(&self) -> Option<&dyn UndoBuffer> {
@@ -487,6 +675,59 @@ impl<Store: TextStore + Default> TextCore<Store> {
             };
         }
     }
+
+    /// Replay a recording of changes, but check each entry
+    /// against the current text before it is applied.
+    ///
+    /// Inserts must hit a grapheme boundary and removes must find
+    /// the recorded text at their byte range. The first entry that
+    /// fails stops the replay, its index is returned with
+    /// [TextError::ReplayConflict]. The entries before it remain
+    /// applied.
+    ///
+    /// This is best-effort only, there is no merging of diverging
+    /// edits.
+    pub fn replay_log_checked(&mut self, replay: &[UndoEntry]) -> Result<(), TextError> {
+        for (idx, replay_entry) in replay.iter().enumerate() {
+            if !self.replay_matches(&replay_entry.operation) {
+                return Err(TextError::ReplayConflict(idx));
+            }
+            self.replay_log(slice::from_ref(replay_entry));
+        }
+        Ok(())
+    }
+
+    /// Can the operation be applied to the current text?
+    fn replay_matches(&self, op: &UndoOp) -> bool {
+        match op {
+            UndoOp::InsertChar { bytes, .. } | UndoOp::InsertStr { bytes, .. } => {
+                self.is_grapheme_boundary(bytes.start)
+            }
+            UndoOp::RemoveChar { bytes, txt, .. } | UndoOp::RemoveStr { bytes, txt, .. } => {
+                bytes.start <= bytes.end
+                    && self.is_grapheme_boundary(bytes.start)
+                    && self.is_grapheme_boundary(bytes.end)
+                    && matches!(self.text.str_slice_byte(bytes.clone()), Ok(v) if v == *txt)
+            }
+            _ => true,
+        }
+    }
+
+    /// Is the byte index at the start of a grapheme or
+    /// at the end of the text.
+    fn is_grapheme_boundary(&self, byte: usize) -> bool {
+        if byte == self.text.len_bytes() {
+            true
+        } else if byte > self.text.len_bytes() {
+            false
+        } else {
+            self.text
+                .byte_to_pos(byte)
+                .and_then(|pos| self.text.byte_range_at(pos))
+                .map(|r| r.start == byte)
+                .unwrap_or(false)
+        }
+    }
 }
 
 impl<Store: TextStore + Default> TextCore<Store> {
@@ -628,6 +869,22 @@ impl<Store: TextStore + Default> TextCore<Store> {
         old_cursor != self.cursor || old_anchor != self.anchor
     }
 
+    /// Set the cursor position.
+    /// Other than [TextCore::set_cursor] this returns an error
+    /// if the position is outside the text instead of capping it.
+    ///
+    /// Returns true, if the cursor actually changed.
+    pub fn try_set_cursor(
+        &mut self,
+        cursor: TextPosition,
+        extend_selection: bool,
+    ) -> Result<bool, TextError> {
+        if cursor.y >= self.len_lines() || cursor.x > self.line_width(cursor.y)? {
+            return Err(TextError::TextPositionOutOfBounds(cursor));
+        }
+        Ok(self.set_cursor(cursor, extend_selection))
+    }
+
     /// Cursor position as grapheme-idx.
     #[inline]
     pub fn cursor(&self) -> TextPosition {
@@ -745,6 +1002,51 @@ impl<Store: TextStore + Default> TextCore<Store> {
         self.text.str_slice_byte(range)
     }
 
+    /// A range of the text as simple html.
+    ///
+    /// Styled text is wrapped in `<span class="style-N">` where N
+    /// is the style-nr. The text itself is escaped.
+    pub fn str_slice_html(&self, range: TextRange) -> Result<String, TextError> {
+        let bytes = self.bytes_at_range(range)?;
+
+        let mut styles = Vec::new();
+        self.styles_in(bytes.clone(), &mut styles);
+
+        let mut bounds = vec![bytes.start, bytes.end];
+        for (r, _) in &styles {
+            bounds.push(r.start.clamp(bytes.start, bytes.end));
+            bounds.push(r.end.clamp(bytes.start, bytes.end));
+        }
+        bounds.sort();
+        bounds.dedup();
+
+        let mut html = String::new();
+        for b in bounds.windows(2) {
+            let part = b[0]..b[1];
+            let txt = self.str_slice_byte(part.clone())?;
+
+            let mut class = String::new();
+            for (r, style) in &styles {
+                if r.start <= part.start && part.end <= r.end {
+                    if !class.is_empty() {
+                        class.push(' ');
+                    }
+                    class.push_str(&format!("style-{}", style));
+                }
+            }
+
+            if class.is_empty() {
+                html_escape(&txt, &mut html);
+            } else {
+                html.push_str(&format!("<span class=\"{}\">", class));
+                html_escape(&txt, &mut html);
+                html.push_str("</span>");
+            }
+        }
+
+        Ok(html)
+    }
+
     /// Iterator for the glyphs of the lines in range.
     /// Glyphs here a grapheme + display length.
     #[inline]
@@ -753,6 +1055,27 @@ impl<Store: TextStore + Default> TextCore<Store> {
         rows: Range<upos_type>,
         screen_offset: u16,
         screen_width: u16,
+    ) -> Result<impl Iterator<Item = Glyph<'_>>, TextError> {
+        self.glyphs_with(
+            rows,
+            screen_offset,
+            screen_width,
+            self.tabs,
+            self.glyph_ctrl,
+        )
+    }
+
+    /// Iterator for the glyphs of the lines in range.
+    ///
+    /// Uses the given tab-width and show_ctrl instead of the
+    /// settings of the core.
+    pub fn glyphs_with(
+        &self,
+        rows: Range<upos_type>,
+        screen_offset: u16,
+        screen_width: u16,
+        tabs: u16,
+        show_ctrl: bool,
     ) -> Result<impl Iterator<Item = Glyph<'_>>, TextError> {
         let iter = self.graphemes(
             TextRange::new((0, rows.start), (0, rows.end)),
@@ -762,8 +1085,10 @@ impl<Store: TextStore + Default> TextCore<Store> {
         let mut it = GlyphIter::new(TextPosition::new(0, rows.start), iter);
         it.set_screen_offset(screen_offset);
         it.set_screen_width(screen_width);
-        it.set_tabs(self.tabs);
-        it.set_show_ctrl(self.glyph_ctrl);
+        it.set_tabs(tabs);
+        it.set_show_ctrl(show_ctrl);
+        it.set_tab_glyph(self.glyph_tab);
+        it.set_space_glyph(self.glyph_space);
         it.set_line_break(self.glyph_line_break);
         Ok(it)
     }
@@ -838,6 +1163,47 @@ impl<Store: TextStore + Default> TextCore<Store> {
     pub fn len_lines(&self) -> upos_type {
         self.text.len_lines()
     }
+
+    /// Length in bytes.
+    #[inline]
+    pub fn len_bytes(&self) -> usize {
+        self.text.len_bytes()
+    }
+
+    /// Length in chars.
+    #[inline]
+    pub fn len_chars(&self) -> usize {
+        self.text.len_chars()
+    }
+
+    /// Statistics for the text.
+    pub fn stats(&self) -> TextStats {
+        let mut stats = TextStats {
+            lines: self.len_lines(),
+            ..Default::default()
+        };
+
+        let mut in_word = false;
+        for g in self
+            .text_graphemes(TextPosition::new(0, 0))
+            .expect("valid_pos")
+        {
+            if !g.is_line_break() {
+                stats.graphemes += 1;
+            }
+            stats.chars += g.grapheme().chars().count();
+            stats.bytes += g.text_bytes().len();
+
+            if g.is_whitespace() {
+                in_word = false;
+            } else if !in_word {
+                in_word = true;
+                stats.words += 1;
+            }
+        }
+
+        stats
+    }
 }
 
 impl<Store: TextStore + Default> TextCore<Store> {
@@ -865,6 +1231,14 @@ impl<Store: TextStore + Default> TextCore<Store> {
         &self.text
     }
 
+    /// Take the text-store out and leave an empty one.
+    ///
+    /// Cursor, anchor and styles are not adjusted, use
+    /// [TextCore::set_text] to put a store back.
+    pub(crate) fn take_text(&mut self) -> Store {
+        std::mem::take(&mut self.text)
+    }
+
     /// Set the text as a TextStore
     /// Clears the styles.
     /// Caps cursor and anchor.
@@ -898,6 +1272,19 @@ impl<Store: TextStore + Default> TextCore<Store> {
         true
     }
 
+    /// Set the text and the styles at once.
+    ///
+    /// The styles are part of the initial state, this doesn't
+    /// create an undo entry for them. The replay-log gets both.
+    pub fn set_text_with_styles(&mut self, t: Store, styles: Vec<(Range<usize>, usize)>) -> bool {
+        self.set_text(t);
+        self.set_styles(styles);
+        if let Some(undo) = &mut self.undo {
+            undo.clear_undo();
+        }
+        true
+    }
+
     /// Auto-quote the selected text.
     #[allow(clippy::needless_bool)]
     pub fn insert_quotes(&mut self, mut sel: TextRange, c: char) -> Result<bool, TextError> {
@@ -1054,6 +1441,33 @@ impl<Store: TextStore + Default> TextCore<Store> {
         Ok(true)
     }
 
+    /// Insert pre-segmented graphemes at position.
+    ///
+    /// Each item must be exactly one grapheme cluster, otherwise
+    /// nothing is inserted and this returns [TextError::InvalidText].
+    /// All graphemes are inserted as a single undo step.
+    ///
+    /// Returns the number of inserted graphemes.
+    pub fn insert_graphemes<'b>(
+        &mut self,
+        pos: TextPosition,
+        graphemes: impl IntoIterator<Item = &'b str>,
+    ) -> Result<usize, TextError> {
+        let mut buf = String::new();
+        let mut n = 0;
+        for g in graphemes {
+            if g.graphemes(true).count() != 1 {
+                return Err(TextError::InvalidText(g.to_string()));
+            }
+            buf.push_str(g);
+            n += 1;
+        }
+        if n > 0 {
+            self.insert_str(pos, &buf)?;
+        }
+        Ok(n)
+    }
+
     /// Remove the previous character
     pub fn remove_prev_char(&mut self, pos: TextPosition) -> Result<bool, TextError> {
         let (sx, sy) = if pos.y == 0 && pos.x == 0 {
@@ -1302,12 +1716,25 @@ impl<Store: TextStore + Default> TextCore<Store> {
             let Some(c) = cursor.next() else {
                 break;
             };
-            last_pos = c.text_bytes().start;
             if c.is_whitespace() {
+                last_pos = c.text_bytes().start;
                 break;
             }
+            last_pos = c.text_bytes().end;
         }
 
         Ok(self.byte_pos(last_pos).expect("valid_pos"))
     }
 }
+
+fn html_escape(txt: &str, buf: &mut String) {
+    for c in txt.chars() {
+        match c {
+            '&' => buf.push_str("&amp;"),
+            '<' => buf.push_str("&lt;"),
+            '>' => buf.push_str("&gt;"),
+            '"' => buf.push_str("&quot;"),
+            c => buf.push(c),
+        }
+    }
+}
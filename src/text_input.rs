@@ -19,10 +19,10 @@ use crate::core::{TextCore, TextString};
 use crate::event::{ReadOnly, TextOutcome};
 use crate::undo_buffer::{UndoBuffer, UndoEntry, UndoVec};
 use crate::{
-    ipos_type, upos_type, Cursor, Glyph, Grapheme, HasScreenCursor, TextError, TextPosition,
-    TextRange, TextStyle,
+    ipos_type, upos_type, CaretShape, Cursor, Glyph, Grapheme, HasScreenCursor, TextError,
+    TextPosition, TextRange, TextStats, TextStyle,
 };
-use crossterm::event::KeyModifiers;
+use crossterm::event::{KeyModifiers, MouseButton, MouseEventKind};
 use rat_event::util::MouseFlags;
 use rat_event::{ct_event, HandleEvent, MouseOnly, Regular};
 use rat_focus::{FocusFlag, HasFocus};
@@ -35,8 +35,13 @@ use ratatui::style::{Style, Stylize};
 use ratatui::widgets::StatefulWidgetRef;
 use ratatui::widgets::{Block, StatefulWidget, Widget};
 use std::borrow::Cow;
-use std::cmp::min;
+use std::cmp::{max, min, Ordering};
+use std::fmt::{Debug, Formatter};
+use std::iter::once;
 use std::ops::Range;
+use std::rc::Rc;
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthChar;
 
 /// Text input widget.
 ///
@@ -49,12 +54,109 @@ pub struct TextInput<'a> {
     style: Style,
     focus_style: Option<Style>,
     select_style: Option<Style>,
+    inactive_select_style: Option<Style>,
     invalid_style: Option<Style>,
+    cursor_style: Option<Style>,
+    extra_cursor_style: Option<Style>,
+    preedit_style: Option<Style>,
+    ctrl_style: Option<Style>,
+    show_ctrl: Option<bool>,
+    selection_markers: Option<(char, char)>,
+    guide_column: Option<upos_type>,
+    guide_style: Option<Style>,
+    placeholder: Option<Cow<'a, str>>,
+    placeholder_style: Option<Style>,
+    placeholder_when: PlaceholderWhen,
+    overflow_indicators: bool,
+    overflow_style: Option<Style>,
     text_style: Vec<Style>,
 }
 
+/// Screen information for a single glyph.
+/// See [TextInputState::glyph_at()]
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct GlyphInfo {
+    /// Grapheme position.
+    pub pos: upos_type,
+    /// Screen column relative to the inner area.
+    pub screen_pos: u16,
+    /// Display width.
+    pub screen_width: u16,
+    /// Byte range in the text.
+    pub text_bytes: Range<usize>,
+}
+
+/// Result of an insert.
+/// See [TextInputState::insert_char_result()]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct InsertResult {
+    /// Number of inserted chars.
+    pub inserted: usize,
+    /// Number of rejected chars.
+    pub rejected: usize,
+}
+
+/// Behaviour of the Tab key.
+/// See [TextInputState::set_tab_action()]
+///
+/// | action    | Tab while editing | Tab that moved the focus here |
+/// |-----------|-------------------|-------------------------------|
+/// | Insert    | inserts a tab     | inserts a tab                 |
+/// | FocusNext | Continue          | Continue                      |
+/// | Smart     | inserts a tab     | ignored                       |
+///
+/// With `FocusNext` the event handler returns [TextOutcome::Continue]
+/// for Tab, so the focus system can move on to the next widget.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum TabAction {
+    /// Always insert a tab.
+    Insert,
+    /// Never insert a tab, leave it to focus handling.
+    FocusNext,
+    /// Insert a tab, except for the Tab that moved the focus
+    /// to this widget.
+    #[default]
+    Smart,
+}
+
+/// When is the placeholder shown.
+/// See [TextInput::placeholder_when()]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum PlaceholderWhen {
+    /// Always. If there is some text the placeholder is shown
+    /// right aligned in the unused area after it, like an inline
+    /// hint. It is left out if it doesn't fit there completely.
+    Always,
+    /// Only if the text is empty and the widget is not focused.
+    Unfocused,
+    /// If the text is empty, focused or not.
+    #[default]
+    Empty,
+}
+
+/// Result of [TextInputState::hit()].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Hit {
+    /// Outside the widget.
+    Outside,
+    /// On the block around the text.
+    Border,
+    /// In the text area, with the grapheme position.
+    Text(upos_type),
+}
+
+/// Granularity for extending the selection with a mouse drag.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum SelectionGranularity {
+    /// Extend by graphemes.
+    #[default]
+    Char,
+    /// Extend by whole words.
+    Word,
+}
+
 /// State for TextInput.
-#[derive(Debug, Clone)]
+#[derive(Clone)]
 pub struct TextInputState {
     /// The whole area with block.
     /// __read only__ renewed with each render.
@@ -66,15 +168,77 @@ pub struct TextInputState {
     /// Display offset
     /// __read+write__
     pub offset: upos_type,
+    /// Columns of context kept visible left and right of the cursor.
+    /// __read+write__
+    pub scroll_off: u16,
+    /// Step for scrolling with Alt+Left/Right and the mouse wheel.
+    /// __read+write__
+    pub scroll_step: upos_type,
     /// Dark offset due to clipping.
     /// __read only__ secondary offset due to clipping.
     pub dark_offset: (u16, u16),
 
     /// Editing core
     pub value: TextCore<TextString>,
+    /// Only navigation, no editing in the event-handler.
+    /// __read+write__
+    pub read_only: bool,
+    /// Text at the last [TextInputState::mark_clean()].
+    /// __read only__
+    pub clean_text: String,
     /// Display as invalid.
     /// __read+write__
     pub invalid: bool,
+    /// Why the value is invalid.
+    /// __read+write__
+    pub invalid_reason: Option<String>,
+    /// Style-nr used by [TextInputState::set_underline_ranges()].
+    /// __read only__
+    pub underline_style: Option<usize>,
+    /// Trim whitespace when the focus is lost.
+    /// __read+write__
+    pub trim_on_blur: bool,
+    /// Truncate inserted text at the first line-break instead
+    /// of replacing line-breaks with a space.
+    /// __read+write__
+    pub truncate_newline: bool,
+    /// Replace inserted line-breaks with this marker.
+    /// __read+write__
+    pub newline_marker: Option<char>,
+    /// Enter and typed line-breaks insert a space.
+    /// __read+write__
+    pub enter_inserts_space: bool,
+    /// Don't insert a space after a space.
+    /// __read+write__
+    pub collapse_spaces: bool,
+    /// Drop zero-width chars that would start the text.
+    /// __read+write__
+    pub reject_leading_combining: bool,
+    /// Maximum length of the text in bytes.
+    /// __read+write__
+    pub max_bytes: Option<usize>,
+    /// Remap chars before they are inserted.
+    /// __read+write__
+    pub char_remap: Option<fn(char) -> Option<char>>,
+    /// Called when an edit is rejected.
+    /// __read+write__
+    pub on_reject: Option<Rc<dyn Fn()>>,
+    /// Behaviour of the Tab key.
+    /// __read+write__
+    pub tab_action: TabAction,
+    /// Backspace removes the spaces back to the previous tab stop.
+    /// __read+write__
+    pub smart_backspace: bool,
+    /// Uncommitted IME composition text. It is rendered at the
+    /// cursor but is not part of the value.
+    /// __read+write__
+    pub preedit: Option<String>,
+    /// Cursor position inside the preedit text in graphemes.
+    /// __read+write__
+    pub preedit_cursor: upos_type,
+    /// Additional carets. Typed chars are inserted at each of them.
+    /// __read+write__
+    pub extra_cursors: Vec<upos_type>,
 
     /// Current focus state.
     /// __read+write__
@@ -83,6 +247,18 @@ pub struct TextInputState {
     /// Mouse selection in progress.
     /// __read+write__
     pub mouse: MouseFlags,
+    /// Relative column of the last mouse drag, if it
+    /// was outside the widget. Used for auto-scrolling.
+    /// __read only__
+    pub drag_overflow: Option<i16>,
+    /// Granularity of the current mouse selection.
+    /// A double-click starts a word selection, a plain click
+    /// resets it to chars.
+    /// __read+write__
+    pub selection_granularity: SelectionGranularity,
+    /// The click that focuses the widget sets the cursor.
+    /// __read+write__
+    pub focus_click_moves_caret: bool,
 
     /// Construct with `..Default::default()`
     pub non_exhaustive: NonExhaustive,
@@ -144,6 +320,14 @@ impl<'a> TextInput<'a> {
         self
     }
 
+    /// Style for the selection when the widget is not focused.
+    /// If this is not set the selection is not shown.
+    #[inline]
+    pub fn inactive_select_style(mut self, style: impl Into<Style>) -> Self {
+        self.inactive_select_style = Some(style.into());
+        self
+    }
+
     /// Style for the invalid indicator.
     /// This is patched onto either base_style or focus_style
     #[inline]
@@ -152,6 +336,124 @@ impl<'a> TextInput<'a> {
         self
     }
 
+    /// Style for the cursor cell.
+    ///
+    /// If set, this style is patched onto the cell at the cursor
+    /// position when the widget is focused. Useful if the terminal
+    /// can't show a hardware cursor.
+    #[inline]
+    pub fn cursor_style(mut self, style: impl Into<Style>) -> Self {
+        self.cursor_style = Some(style.into());
+        self
+    }
+
+    /// Style for the extra cursors.
+    /// Defaults to reversed.
+    #[inline]
+    pub fn extra_cursor_style(mut self, style: impl Into<Style>) -> Self {
+        self.extra_cursor_style = Some(style.into());
+        self
+    }
+
+    /// Show control characters.
+    ///
+    /// This is set on the state when rendering. See
+    /// [TextInputState::set_show_ctrl()].
+    #[inline]
+    pub fn show_ctrl(mut self, show_ctrl: bool) -> Self {
+        self.show_ctrl = Some(show_ctrl);
+        self
+    }
+
+    /// Style for visible control characters.
+    #[inline]
+    pub fn ctrl_style(mut self, style: impl Into<Style>) -> Self {
+        self.ctrl_style = Some(style.into());
+        self
+    }
+
+    /// Style for the IME preedit text.
+    /// Defaults to the base style underlined.
+    #[inline]
+    pub fn preedit_style(mut self, style: impl Into<Style>) -> Self {
+        self.preedit_style = Some(style.into());
+        self
+    }
+
+    /// Markers drawn over the first and the last cell of the
+    /// selection, e.g. `('▏', '▕')`.
+    ///
+    /// The markers replace the symbol of the cell, the layout
+    /// doesn't change. If only one cell is selected, the end marker
+    /// is shown.
+    #[inline]
+    pub fn selection_markers(mut self, start: char, end: char) -> Self {
+        self.selection_markers = Some((start, end));
+        self
+    }
+
+    /// Paint everything at and after this grapheme column with
+    /// the guide style. This is a visual ruler for a recommended
+    /// length, it doesn't limit the input.
+    #[inline]
+    pub fn guide_column(mut self, column: upos_type) -> Self {
+        self.guide_column = Some(column);
+        self
+    }
+
+    /// Style for the cells after the guide column.
+    /// Defaults to dim.
+    ///
+    /// It is applied before the text styles and the selection.
+    #[inline]
+    pub fn guide_style(mut self, style: impl Into<Style>) -> Self {
+        self.guide_style = Some(style.into());
+        self
+    }
+
+    /// Hint text shown in place of the text.
+    /// See [TextInput::placeholder_when()].
+    #[inline]
+    pub fn placeholder(mut self, placeholder: impl Into<Cow<'a, str>>) -> Self {
+        self.placeholder = Some(placeholder.into());
+        self
+    }
+
+    /// Style for the placeholder.
+    /// Defaults to dim.
+    #[inline]
+    pub fn placeholder_style(mut self, style: impl Into<Style>) -> Self {
+        self.placeholder_style = Some(style.into());
+        self
+    }
+
+    /// When is the placeholder shown.
+    /// Defaults to [PlaceholderWhen::Empty].
+    #[inline]
+    pub fn placeholder_when(mut self, when: PlaceholderWhen) -> Self {
+        self.placeholder_when = when;
+        self
+    }
+
+    /// Show `‹` and `›` at the left and right edge if the text
+    /// is scrolled out of view on that side.
+    ///
+    /// The markers replace the glyph at the edge, except for the
+    /// cell with the caret.
+    #[inline]
+    pub fn overflow_indicators(mut self, show: bool) -> Self {
+        self.overflow_indicators = show;
+        self
+    }
+
+    /// Style for the overflow indicators.
+    /// Defaults to dim.
+    #[inline]
+    pub fn overflow_style(mut self, style: impl Into<Style>) -> Self {
+        self.overflow_style = Some(style.into());
+        self
+    }
+
     /// List of text-styles.
     ///
     /// Use [TextInputState::add_style()] to refer a text range to
@@ -167,6 +469,15 @@ impl<'a> TextInput<'a> {
         self.block = Some(block);
         self
     }
+
+    /// Inner area for the given area.
+    ///
+    /// This is the area render will use for the text.
+    /// Can be used for layout before rendering.
+    #[inline]
+    pub fn inner(&self, area: Rect) -> Rect {
+        self.block.inner_if_some(area)
+    }
 }
 
 #[cfg(feature = "unstable-widget-ref")]
@@ -189,6 +500,9 @@ impl<'a> StatefulWidget for TextInput<'a> {
 fn render_ref(widget: &TextInput<'_>, area: Rect, buf: &mut Buffer, state: &mut TextInputState) {
     state.area = area;
     state.inner = widget.block.inner_if_some(area);
+    if let Some(show_ctrl) = widget.show_ctrl {
+        state.value.set_glyph_ctrl(show_ctrl);
+    }
 
     widget.block.render(area, buf);
 
@@ -198,6 +512,12 @@ fn render_ref(widget: &TextInput<'_>, area: Rect, buf: &mut Buffer, state: &mut
         // noop
         return;
     }
+    // the area may be partially outside the buffer, e.g. when
+    // the parent scrolls. all cell access below goes through
+    // cell_mut() and clips against the buffer.
+    if !inner.intersects(*buf.area()) {
+        return;
+    }
 
     let focus_style = if let Some(focus_style) = widget.focus_style {
         focus_style
@@ -225,22 +545,51 @@ fn render_ref(widget: &TextInput<'_>, area: Rect, buf: &mut Buffer, state: &mut
             (focus_style, select_style)
         }
     } else {
+        let select_style = if let Some(inactive_select_style) = widget.inactive_select_style {
+            widget.style.patch(inactive_select_style)
+        } else {
+            widget.style
+        };
         if state.invalid {
             (
                 widget.style.patch(invalid_style),
-                widget.style.patch(invalid_style),
+                select_style.patch(invalid_style),
             )
         } else {
-            (widget.style, widget.style)
+            (widget.style, select_style)
         }
     };
 
+    let guide_style = if let Some(guide_style) = widget.guide_style {
+        style.patch(guide_style)
+    } else {
+        style.dim()
+    };
+    // screen column of the guide
+    let guide_x = widget.guide_column.map(|guide| {
+        if guide < state.offset() {
+            0
+        } else if guide <= state.len() {
+            state.col_to_screen(guide).unwrap_or_default()
+        } else {
+            let extra = min(guide - state.len(), u16::MAX as upos_type) as u16;
+            state
+                .col_to_screen(state.len())
+                .unwrap_or_default()
+                .saturating_add(extra)
+        }
+    });
+
     // set base style
     for y in inner.top()..inner.bottom() {
         for x in inner.left()..inner.right() {
             if let Some(cell) = buf.cell_mut((x, y)) {
                 cell.reset();
-                cell.set_style(style);
+                if guide_x.is_some_and(|v| x - inner.x >= v) {
+                    cell.set_style(guide_style);
+                } else {
+                    cell.set_style(style);
+                }
             }
         }
     }
@@ -255,13 +604,21 @@ fn render_ref(widget: &TextInput<'_>, area: Rect, buf: &mut Buffer, state: &mut
     let selection = state.selection();
     let mut styles = Vec::new();
 
+    // glyphs after the cursor are moved to make room for the preedit.
+    let cursor = state.cursor();
+    let preedit_width = state.preedit_width(upos_type::MAX);
+
     let glyph_iter = state
         .value
         .glyphs(0..1, ox, inner.width)
         .expect("valid_offset");
     for g in glyph_iter {
         if g.screen_width() > 0 {
-            let mut style = style;
+            let mut style = if widget.guide_column.is_some_and(|v| g.pos().x >= v) {
+                guide_style
+            } else {
+                style
+            };
             styles.clear();
             state
                 .value
@@ -271,21 +628,46 @@ fn render_ref(widget: &TextInput<'_>, area: Rect, buf: &mut Buffer, state: &mut
                     style = style.patch(*s);
                 }
             }
+            // control chars
+            if let Some(ctrl_style) = widget.ctrl_style {
+                if state.value.glyph_ctrl()
+                    && state.text()[g.text_bytes()].starts_with(|c: char| c.is_control())
+                {
+                    style = style.patch(ctrl_style);
+                }
+            }
             // selection
             if selection.contains(&g.pos().x) {
                 style = style.patch(select_style);
             };
 
             // relative screen-pos of the glyph
-            let screen_pos = g.screen_pos();
+            let mut screen_pos = g.screen_pos();
+            if g.pos().x >= cursor {
+                screen_pos.0 += preedit_width;
+                if screen_pos.0 + g.screen_width() > inner.width {
+                    continue;
+                }
+            }
+
+            if screen_pos.0 >= inner.width {
+                continue;
+            }
+            // a wide glyph straddling the right edge is replaced
+            // with spaces within inner.
+            let visible_width = min(g.screen_width(), inner.width - screen_pos.0);
 
             // render glyph
             if let Some(cell) = buf.cell_mut((inner.x + screen_pos.0, inner.y + screen_pos.1)) {
-                cell.set_symbol(g.glyph());
+                if visible_width < g.screen_width() {
+                    cell.set_symbol(" ");
+                } else {
+                    cell.set_symbol(g.glyph());
+                }
                 cell.set_style(style);
             }
             // clear the reset of the cells to avoid interferences.
-            for d in 1..g.screen_width() {
+            for d in 1..visible_width {
                 if let Some(cell) =
                     buf.cell_mut((inner.x + screen_pos.0 + d, inner.y + screen_pos.1))
                 {
@@ -293,29 +675,211 @@ fn render_ref(widget: &TextInput<'_>, area: Rect, buf: &mut Buffer, state: &mut
                     cell.set_style(style);
                 }
             }
+            // selection markers
+            if let Some((start_marker, end_marker)) = widget.selection_markers {
+                if g.pos().x == selection.start && !selection.is_empty() {
+                    if let Some(cell) =
+                        buf.cell_mut((inner.x + screen_pos.0, inner.y + screen_pos.1))
+                    {
+                        cell.set_char(start_marker);
+                    }
+                }
+                if g.pos().x + 1 == selection.end && !selection.is_empty() {
+                    let x = screen_pos.0 + visible_width - 1;
+                    if let Some(cell) = buf.cell_mut((inner.x + x, inner.y + screen_pos.1)) {
+                        cell.set_char(end_marker);
+                    }
+                }
+            }
+        }
+    }
+
+    // placeholder
+    if let Some(placeholder) = &widget.placeholder {
+        let show = match widget.placeholder_when {
+            PlaceholderWhen::Always => true,
+            PlaceholderWhen::Unfocused => state.is_empty() && !state.focus.get(),
+            PlaceholderWhen::Empty => state.is_empty(),
+        };
+        if show && state.preedit.is_none() {
+            let placeholder_style = if let Some(placeholder_style) = widget.placeholder_style {
+                style.patch(placeholder_style)
+            } else {
+                style.dim()
+            };
+            let screen_x = state.col_to_screen(state.len()).and_then(|screen_x| {
+                if state.is_empty() {
+                    Some(screen_x)
+                } else {
+                    // right aligned in the unused area, with a gap
+                    // after the text. Not shown if it doesn't fit.
+                    let width = unicode_display_width::width(placeholder) as u16;
+                    inner.width.checked_sub(width).filter(|v| *v > screen_x)
+                }
+            });
+            if let Some(mut screen_x) = screen_x {
+                for g in placeholder.graphemes(true) {
+                    let width = unicode_display_width::width(g) as u16;
+                    if screen_x + width > inner.width {
+                        break;
+                    }
+                    if let Some(cell) = buf.cell_mut((inner.x + screen_x, inner.y)) {
+                        cell.set_symbol(g);
+                        cell.set_style(placeholder_style);
+                    }
+                    for d in 1..width {
+                        if let Some(cell) = buf.cell_mut((inner.x + screen_x + d, inner.y)) {
+                            cell.reset();
+                            cell.set_style(placeholder_style);
+                        }
+                    }
+                    screen_x += width;
+                }
+            }
+        }
+    }
+
+    // preedit
+    if let Some(preedit) = &state.preedit {
+        let preedit_style = if let Some(preedit_style) = widget.preedit_style {
+            style.patch(preedit_style)
+        } else {
+            style.underlined()
+        };
+        if let Some(mut screen_x) = state.col_to_screen(cursor) {
+            for g in preedit.graphemes(true) {
+                let width = unicode_display_width::width(g) as u16;
+                if screen_x + width > inner.width {
+                    break;
+                }
+                if let Some(cell) = buf.cell_mut((inner.x + screen_x, inner.y)) {
+                    cell.set_symbol(g);
+                    cell.set_style(preedit_style);
+                }
+                for d in 1..width {
+                    if let Some(cell) = buf.cell_mut((inner.x + screen_x + d, inner.y)) {
+                        cell.reset();
+                        cell.set_style(preedit_style);
+                    }
+                }
+                screen_x += width;
+            }
+        }
+    }
+
+    // overflow indicators
+    if widget.overflow_indicators {
+        let overflow_style = if let Some(overflow_style) = widget.overflow_style {
+            style.patch(overflow_style)
+        } else {
+            style.dim()
+        };
+        let caret = if state.focus.get() {
+            state.caret_position()
+        } else {
+            None
+        };
+        let left = state.offset() > 0;
+        let right = state.total_display_width() as upos_type
+            > state.display_offset() + inner.width as upos_type;
+        for (show, x, marker) in [
+            (left, inner.left(), "\u{2039}"),
+            (right, inner.right() - 1, "\u{203A}"),
+        ] {
+            if !show || caret == Some((x, inner.y)) {
+                continue;
+            }
+            // don't leave half of a wide glyph.
+            if x > inner.left() {
+                if let Some(cell) = buf.cell_mut((x - 1, inner.y)) {
+                    if unicode_display_width::width(cell.symbol()) > 1 {
+                        cell.set_symbol(" ");
+                    }
+                }
+            }
+            if let Some(cell) = buf.cell_mut((x, inner.y)) {
+                cell.set_symbol(marker);
+                cell.set_style(overflow_style);
+            }
+        }
+    }
+
+    // extra cursors
+    if state.focus.get() {
+        let extra_cursor_style = if let Some(extra_cursor_style) = widget.extra_cursor_style {
+            extra_cursor_style
+        } else {
+            Style::default().reversed()
+        };
+        for pos in &state.extra_cursors {
+            if let Some(screen_x) = state.col_to_screen(*pos) {
+                if screen_x < inner.width {
+                    if let Some(cell) = buf.cell_mut((inner.x + screen_x, inner.y)) {
+                        cell.set_style(extra_cursor_style);
+                    }
+                }
+            }
+        }
+    }
+
+    // cursor
+    if let Some(cursor_style) = widget.cursor_style {
+        if state.focus.get() {
+            if let Some(cursor) = state.caret_position() {
+                if inner.contains(cursor.into()) {
+                    if let Some(cell) = buf.cell_mut(cursor) {
+                        cell.set_style(cursor_style);
+                    }
+                }
+            }
         }
     }
 }
 
 impl Default for TextInputState {
     fn default() -> Self {
-        let mut value = TextCore::new(
+        Self::with_core(TextCore::new(
             Some(Box::new(UndoVec::new(99))),
             Some(Box::new(LocalClipboard::new())),
-        );
-        value.set_glyph_line_break(false);
+        ))
+    }
+}
 
-        Self {
-            area: Default::default(),
-            inner: Default::default(),
-            offset: 0,
-            dark_offset: (0, 0),
-            value,
-            invalid: false,
-            focus: Default::default(),
-            mouse: Default::default(),
-            non_exhaustive: NonExhaustive,
-        }
+impl Debug for TextInputState {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TextInputState")
+            .field("area", &self.area)
+            .field("inner", &self.inner)
+            .field("offset", &self.offset)
+            .field("scroll_off", &self.scroll_off)
+            .field("scroll_step", &self.scroll_step)
+            .field("dark_offset", &self.dark_offset)
+            .field("value", &self.value)
+            .field("read_only", &self.read_only)
+            .field("clean_text", &self.clean_text)
+            .field("invalid", &self.invalid)
+            .field("invalid_reason", &self.invalid_reason)
+            .field("underline_style", &self.underline_style)
+            .field("trim_on_blur", &self.trim_on_blur)
+            .field("truncate_newline", &self.truncate_newline)
+            .field("newline_marker", &self.newline_marker)
+            .field("enter_inserts_space", &self.enter_inserts_space)
+            .field("collapse_spaces", &self.collapse_spaces)
+            .field("reject_leading_combining", &self.reject_leading_combining)
+            .field("max_bytes", &self.max_bytes)
+            .field("char_remap", &self.char_remap)
+            .field("on_reject", &self.on_reject.is_some())
+            .field("tab_action", &self.tab_action)
+            .field("smart_backspace", &self.smart_backspace)
+            .field("preedit", &self.preedit)
+            .field("preedit_cursor", &self.preedit_cursor)
+            .field("extra_cursors", &self.extra_cursors)
+            .field("focus", &self.focus)
+            .field("mouse", &self.mouse)
+            .field("drag_overflow", &self.drag_overflow)
+            .field("selection_granularity", &self.selection_granularity)
+            .field("focus_click_moves_caret", &self.focus_click_moves_caret)
+            .finish()
     }
 }
 
@@ -341,10 +905,116 @@ impl TextInputState {
         }
     }
 
+    /// New state with the given text.
+    /// The cursor is at the start of the text.
+    pub fn from_text<S: Into<String>>(text: S) -> Self {
+        let mut s = Self::default();
+        s.set_text(text);
+        s
+    }
+
+    /// New state with the given text.
+    /// The cursor is at the end of the text.
+    pub fn from_text_cursor_end<S: Into<String>>(text: S) -> Self {
+        let mut s = Self::from_text(text);
+        s.set_cursor(s.len(), false);
+        s
+    }
+
+    /// New named state with the given text.
+    /// The cursor is at the start of the text.
+    pub fn named_with_text<S: Into<String>>(name: &str, text: S) -> Self {
+        let mut s = Self::named(name);
+        s.set_text(text);
+        s
+    }
+
+    /// New state for displaying a value only.
+    ///
+    /// This has no undo-buffer and no clipboard, and it is read-only.
+    /// Undo/redo and the clipboard functions do nothing and return
+    /// false, and the event-handler only handles navigation.
+    /// The value can still be changed with the functions of this
+    /// state.
+    pub fn display_only() -> Self {
+        let mut s = Self::with_core(TextCore::new(None, None));
+        s.read_only = true;
+        s
+    }
+
+    fn with_core(mut value: TextCore<TextString>) -> Self {
+        value.set_glyph_line_break(false);
+
+        Self {
+            area: Default::default(),
+            inner: Default::default(),
+            offset: 0,
+            scroll_off: 0,
+            scroll_step: 1,
+            dark_offset: (0, 0),
+            value,
+            read_only: false,
+            clean_text: Default::default(),
+            invalid: false,
+            invalid_reason: None,
+            underline_style: None,
+            trim_on_blur: false,
+            truncate_newline: false,
+            newline_marker: None,
+            enter_inserts_space: false,
+            collapse_spaces: false,
+            reject_leading_combining: false,
+            max_bytes: None,
+            char_remap: None,
+            on_reject: None,
+            tab_action: Default::default(),
+            smart_backspace: false,
+            preedit: None,
+            preedit_cursor: 0,
+            extra_cursors: Vec::new(),
+            focus: Default::default(),
+            mouse: Default::default(),
+            drag_overflow: None,
+            selection_granularity: Default::default(),
+            focus_click_moves_caret: false,
+            non_exhaustive: NonExhaustive,
+        }
+    }
+
+    /// Only navigation, no editing in the event-handler.
+    #[inline]
+    pub fn set_read_only(&mut self, read_only: bool) {
+        self.read_only = read_only;
+    }
+
+    /// Only navigation, no editing in the event-handler.
+    #[inline]
+    pub fn read_only(&self) -> bool {
+        self.read_only
+    }
+
+    /// Remember the current text as the unchanged state.
+    pub fn mark_clean(&mut self) {
+        self.clean_text.clear();
+        self.clean_text.push_str(self.value.text().as_str());
+    }
+
+    /// Has the text changed since the last [TextInputState::mark_clean()]?
+    ///
+    /// This compares the text, so changing the text back
+    /// to the clean state makes it clean again.
+    pub fn is_dirty(&self) -> bool {
+        self.clean_text != self.text()
+    }
+
     /// Renders the widget in invalid style.
+    /// Setting this to false clears the invalid reason too.
     #[inline]
     pub fn set_invalid(&mut self, invalid: bool) {
         self.invalid = invalid;
+        if !invalid {
+            self.invalid_reason = None;
+        }
     }
 
     /// Renders the widget in invalid style.
@@ -352,46 +1022,474 @@ impl TextInputState {
     pub fn get_invalid(&self) -> bool {
         self.invalid
     }
-}
 
-impl TextInputState {
-    /// Clipboard
+    /// Marks the value as invalid with a human-readable reason,
+    /// or as valid with None.
+    ///
+    /// The reason is not rendered, it's there for the application
+    /// to show as a tooltip or in a status line.
     #[inline]
-    pub fn set_clipboard(&mut self, clip: Option<impl Clipboard + 'static>) {
-        match clip {
-            None => self.value.set_clipboard(None),
-            Some(v) => self.value.set_clipboard(Some(Box::new(v))),
-        }
+    pub fn set_invalid_reason(&mut self, reason: Option<String>) {
+        self.invalid = reason.is_some();
+        self.invalid_reason = reason;
     }
 
-    /// Clipboard
+    /// Why the value is invalid.
     #[inline]
-    pub fn clipboard(&self) -> Option<&dyn Clipboard> {
-        self.value.clipboard()
+    pub fn invalid_reason(&self) -> Option<&str> {
+        self.invalid_reason.as_deref()
     }
 
-    /// Copy to internal buffer
+    /// Trim leading and trailing whitespace when the focus is lost.
+    ///
+    /// This is done in [handle_events] when [FocusFlag::lost()] is set,
+    /// so the focus changes must be routed through rat-focus for this
+    /// to trigger. Otherwise call [TextInputState::trim()] yourself.
     #[inline]
-    pub fn copy_to_clip(&mut self) -> bool {
-        let Some(clip) = self.value.clipboard() else {
-            return false;
-        };
-
-        _ = clip.set_string(self.selected_text().as_ref());
-        false
+    pub fn set_trim_on_blur(&mut self, trim: bool) {
+        self.trim_on_blur = trim;
     }
 
-    /// Cut to internal buffer
+    /// Trim leading and trailing whitespace when the focus is lost.
     #[inline]
-    pub fn cut_to_clip(&mut self) -> bool {
-        let Some(clip) = self.value.clipboard() else {
-            return false;
-        };
-
-        match clip.set_string(self.selected_text().as_ref()) {
-            Ok(_) => self.delete_range(self.selection()),
-            Err(_) => false,
-        }
+    pub fn trim_on_blur(&self) -> bool {
+        self.trim_on_blur
+    }
+
+    /// Line-breaks can't be inserted, they are replaced with a
+    /// single space. If this is set the inserted text is truncated
+    /// at the first line-break instead.
+    ///
+    /// This applies to [TextInputState::insert_str()] and
+    /// [TextInputState::paste_from_clip()].
+    #[inline]
+    pub fn set_truncate_newline(&mut self, truncate: bool) {
+        self.truncate_newline = truncate;
+    }
+
+    /// Truncate inserted text at the first line-break.
+    #[inline]
+    pub fn truncate_newline(&self) -> bool {
+        self.truncate_newline
+    }
+
+    /// Line-breaks in inserted text are replaced with this marker,
+    /// e.g. '␊'. [TextInputState::text_expanded()] turns the markers
+    /// back into line-breaks.
+    ///
+    /// This takes precedence over [TextInputState::set_truncate_newline()].
+    ///
+    /// This applies to [TextInputState::insert_str()] and
+    /// [TextInputState::paste_from_clip()].
+    #[inline]
+    pub fn set_newline_marker(&mut self, marker: Option<char>) {
+        self.newline_marker = marker;
+    }
+
+    /// Marker for line-breaks.
+    #[inline]
+    pub fn newline_marker(&self) -> Option<char> {
+        self.newline_marker
+    }
+
+    /// Enter inserts a space instead of being ignored.
+    /// This also applies to a line-break given to
+    /// [TextInputState::insert_char()].
+    ///
+    /// By default Enter is not used by the widget, and the
+    /// application can use it to submit the value. With this
+    /// flag set, Enter is consumed and can't be used for that.
+    #[inline]
+    pub fn set_enter_inserts_space(&mut self, enter_inserts_space: bool) {
+        self.enter_inserts_space = enter_inserts_space;
+    }
+
+    /// Enter inserts a space.
+    #[inline]
+    pub fn enter_inserts_space(&self) -> bool {
+        self.enter_inserts_space
+    }
+
+    /// Collapse consecutive spaces when inserting.
+    ///
+    /// A space after a space is dropped, and runs of spaces in
+    /// [TextInputState::insert_str()] are reduced to one space.
+    /// Text that is already there is not changed.
+    #[inline]
+    pub fn set_collapse_spaces(&mut self, collapse_spaces: bool) {
+        self.collapse_spaces = collapse_spaces;
+    }
+
+    /// Collapse consecutive spaces when inserting.
+    #[inline]
+    pub fn collapse_spaces(&self) -> bool {
+        self.collapse_spaces
+    }
+
+    /// Drop inserted combining marks and other zero-width chars
+    /// if there is no base character before them. This is the case
+    /// at the start of the text and after a tab.
+    ///
+    /// This applies to [TextInputState::insert_char()] and
+    /// [TextInputState::insert_str()]. Default is false.
+    #[inline]
+    pub fn set_reject_leading_combining(&mut self, reject: bool) {
+        self.reject_leading_combining = reject;
+    }
+
+    /// Drop combining marks without a base character.
+    #[inline]
+    pub fn reject_leading_combining(&self) -> bool {
+        self.reject_leading_combining
+    }
+
+    /// Maximum length of the text in bytes.
+    ///
+    /// Inserts that would exceed the limit are cut at the last
+    /// grapheme that still fits, a single char is rejected. An
+    /// existing longer text is not truncated.
+    ///
+    /// This applies to all the insert functions, including
+    /// [TextInputState::insert_char_at()], and paste. It doesn't
+    /// apply to [TextInputState::set_text()].
+    #[inline]
+    pub fn set_max_bytes(&mut self, max_bytes: Option<usize>) {
+        self.max_bytes = max_bytes;
+    }
+
+    /// Maximum length of the text in bytes.
+    #[inline]
+    pub fn max_bytes(&self) -> Option<usize> {
+        self.max_bytes
+    }
+
+    /// Bytes that can still be inserted, after the selection
+    /// has been replaced.
+    fn byte_budget(&self) -> usize {
+        if let Some(max_bytes) = self.max_bytes {
+            let selected = self.bytes_at_range(self.selection()).len();
+            max_bytes.saturating_sub(self.len_bytes() - selected)
+        } else {
+            usize::MAX
+        }
+    }
+
+    /// Bytes needed by a tab at the given position.
+    fn tab_bytes(&self, pos: upos_type) -> usize {
+        if self.expand_tabs() {
+            let tabs = max(self.tab_width(), 1) as upos_type;
+            (tabs - pos % tabs) as usize
+        } else {
+            1
+        }
+    }
+
+    /// A space inserted at the given position would follow a space.
+    fn after_space(&self, pos: upos_type) -> bool {
+        if !self.collapse_spaces {
+            return false;
+        }
+        match self.text_graphemes(pos).prev() {
+            None => false,
+            Some(g) => &self.text()[g.text_bytes()] == " ",
+        }
+    }
+
+    /// Inserting at the given position would start a new
+    /// cluster without a base character.
+    fn rejects_combining(&self, pos: upos_type) -> bool {
+        if !self.reject_leading_combining {
+            return false;
+        }
+        match self.text_graphemes(pos).prev() {
+            None => true,
+            Some(g) => &self.text()[g.text_bytes()] == "\t",
+        }
+    }
+
+    /// Remap chars before they are inserted with [TextInputState::insert_char()].
+    /// Returning None drops the char.
+    ///
+    /// This can be used to map ',' to '.' for a decimal input,
+    /// or to normalize fullwidth digits.
+    #[inline]
+    pub fn set_char_remap(&mut self, remap: Option<fn(char) -> Option<char>>) {
+        self.char_remap = remap;
+    }
+
+    /// Remap chars before they are inserted.
+    #[inline]
+    pub fn char_remap(&self) -> Option<fn(char) -> Option<char>> {
+        self.char_remap
+    }
+
+    /// Called when an edit is rejected.
+    ///
+    /// This happens when
+    /// * the char remap drops a char,
+    /// * a line-break is typed,
+    /// * pasted text is truncated at a line-break,
+    /// * [TextInputState::try_insert_graphemes()] gets invalid graphemes.
+    ///
+    /// Edits that do nothing at the start or end of the text don't
+    /// count as rejected.
+    ///
+    /// Can be used to ring the bell or flash the widget.
+    #[inline]
+    pub fn set_on_reject(&mut self, on_reject: Option<Rc<dyn Fn()>>) {
+        self.on_reject = on_reject;
+    }
+
+    /// Called when an edit is rejected.
+    #[inline]
+    pub fn on_reject(&self) -> Option<&Rc<dyn Fn()>> {
+        self.on_reject.as_ref()
+    }
+
+    /// Call on_reject.
+    fn reject(&self) {
+        if let Some(on_reject) = &self.on_reject {
+            on_reject();
+        }
+    }
+
+    /// Behaviour of the Tab key.
+    #[inline]
+    pub fn set_tab_action(&mut self, tab_action: TabAction) {
+        self.tab_action = tab_action;
+    }
+
+    /// Behaviour of the Tab key.
+    #[inline]
+    pub fn tab_action(&self) -> TabAction {
+        self.tab_action
+    }
+
+    /// The mouse click that focuses the widget also sets the
+    /// cursor to the clicked position.
+    ///
+    /// By default this click is ignored, so focusing with the
+    /// mouse keeps the current cursor and selection.
+    #[inline]
+    pub fn set_focus_click_moves_caret(&mut self, moves: bool) {
+        self.focus_click_moves_caret = moves;
+    }
+
+    /// The mouse click that focuses the widget also sets the cursor.
+    #[inline]
+    pub fn focus_click_moves_caret(&self) -> bool {
+        self.focus_click_moves_caret
+    }
+
+    /// Show control characters.
+    #[inline]
+    pub fn set_show_ctrl(&mut self, show_ctrl: bool) {
+        self.value.set_glyph_ctrl(show_ctrl);
+    }
+
+    /// Show control characters.
+    pub fn show_ctrl(&self) -> bool {
+        self.value.glyph_ctrl()
+    }
+
+    /// Set tab-width. Only for new inputs.
+    #[inline]
+    pub fn set_tab_width(&mut self, tabs: u16) {
+        self.value.set_tab_width(tabs);
+    }
+
+    /// Tab-width
+    #[inline]
+    pub fn tab_width(&self) -> u16 {
+        self.value.tab_width()
+    }
+
+    /// Expand tabs to spaces. Only for new inputs.
+    #[inline]
+    pub fn set_expand_tabs(&mut self, expand: bool) {
+        self.value.set_expand_tabs(expand);
+    }
+
+    /// Expand tabs to spaces. Only for new inputs.
+    #[inline]
+    pub fn expand_tabs(&self) -> bool {
+        self.value.expand_tabs()
+    }
+
+    /// Backspace removes the spaces back to the previous tab stop,
+    /// if the text before the cursor is only spaces up to there.
+    /// This is recorded as a single undo step.
+    #[inline]
+    pub fn set_smart_backspace(&mut self, smart: bool) {
+        self.smart_backspace = smart;
+    }
+
+    /// Backspace removes the spaces back to the previous tab stop.
+    #[inline]
+    pub fn smart_backspace(&self) -> bool {
+        self.smart_backspace
+    }
+
+    /// Set the IME preedit (composition) text.
+    ///
+    /// The preedit is rendered inline at the cursor with the
+    /// preedit style, but it is not part of the value.
+    /// The preedit cursor is set to the end of the text.
+    ///
+    /// The expected flow for an IME is
+    /// * while composing call `set_preedit(Some(..))` and
+    ///   [TextInputState::set_preedit_cursor()] with each update.
+    /// * on commit call [TextInputState::insert_str()] with the
+    ///   composed text and `set_preedit(None)`.
+    /// * on cancel call `set_preedit(None)`.
+    pub fn set_preedit(&mut self, preedit: Option<String>) {
+        self.preedit_cursor = preedit
+            .as_ref()
+            .map(|v| v.graphemes(true).count() as upos_type)
+            .unwrap_or_default();
+        self.preedit = preedit;
+        self.scroll_cursor_to_visible();
+    }
+
+    /// IME preedit text.
+    #[inline]
+    pub fn preedit(&self) -> Option<&str> {
+        self.preedit.as_deref()
+    }
+
+    /// Set the cursor inside the preedit text.
+    /// The position is in graphemes and will be clamped.
+    pub fn set_preedit_cursor(&mut self, pos: upos_type) {
+        let len = self
+            .preedit
+            .as_ref()
+            .map(|v| v.graphemes(true).count() as upos_type)
+            .unwrap_or_default();
+        self.preedit_cursor = min(pos, len);
+        self.scroll_cursor_to_visible();
+    }
+
+    /// Cursor inside the preedit text.
+    #[inline]
+    pub fn preedit_cursor(&self) -> upos_type {
+        self.preedit_cursor
+    }
+
+    /// Add an extra caret.
+    ///
+    /// Chars typed with [TextInputState::insert_char()] are inserted
+    /// at the cursor and at every extra caret. Any other edit,
+    /// including replacing a selection, removes the extra carets.
+    ///
+    /// Returns false if the position is already a caret.
+    pub fn add_cursor(&mut self, pos: upos_type) -> bool {
+        let pos = min(pos, self.len());
+        if pos == self.cursor() || self.extra_cursors.contains(&pos) {
+            false
+        } else {
+            self.extra_cursors.push(pos);
+            self.extra_cursors.sort();
+            true
+        }
+    }
+
+    /// Remove all extra carets.
+    pub fn clear_extra_cursors(&mut self) -> bool {
+        let changed = !self.extra_cursors.is_empty();
+        self.extra_cursors.clear();
+        changed
+    }
+
+    /// Extra carets.
+    #[inline]
+    pub fn extra_cursors(&self) -> &[upos_type] {
+        &self.extra_cursors
+    }
+
+    /// Insert the char at the cursor and at every extra caret.
+    fn insert_char_multi(&mut self, c: char) -> Result<(), TextError> {
+        let mut carets = self.extra_cursors.clone();
+        carets.push(self.cursor());
+        carets.sort();
+
+        self.value.begin_undo_seq();
+        let mut r = Ok(true);
+        for pos in carets.iter().rev() {
+            r = self.value.insert_char(TextPosition::new(*pos, 0), c);
+            if r.is_err() {
+                break;
+            }
+        }
+        self.value.end_undo_seq();
+        r?;
+
+        // the cursor is moved by the core, the extra carets here.
+        for e in self.extra_cursors.iter_mut() {
+            *e += carets.iter().filter(|v| **v <= *e).count() as upos_type;
+        }
+        Ok(())
+    }
+
+    /// Display width of the preedit text up to the given grapheme.
+    fn preedit_width(&self, upto: upos_type) -> u16 {
+        if let Some(preedit) = &self.preedit {
+            preedit
+                .graphemes(true)
+                .take(upto as usize)
+                .map(|v| unicode_display_width::width(v) as u16)
+                .sum()
+        } else {
+            0
+        }
+    }
+}
+
+impl TextInputState {
+    /// Clipboard
+    #[inline]
+    pub fn set_clipboard(&mut self, clip: Option<impl Clipboard + 'static>) {
+        match clip {
+            None => self.value.set_clipboard(None),
+            Some(v) => self.value.set_clipboard(Some(Box::new(v))),
+        }
+    }
+
+    /// Clipboard
+    #[inline]
+    pub fn clipboard(&self) -> Option<&dyn Clipboard> {
+        self.value.clipboard()
+    }
+
+    /// Copy to internal buffer
+    #[inline]
+    pub fn copy_to_clip(&mut self) -> bool {
+        let Some(clip) = self.value.clipboard() else {
+            return false;
+        };
+
+        _ = clip.set_string(self.selected_text().as_ref());
+        if let Ok(html) = self.value.str_slice_html(self.value.selection()) {
+            _ = clip.set_html(&html);
+        }
+        false
+    }
+
+    /// Cut to internal buffer
+    #[inline]
+    pub fn cut_to_clip(&mut self) -> bool {
+        let Some(clip) = self.value.clipboard() else {
+            return false;
+        };
+
+        match clip.set_string(self.selected_text().as_ref()) {
+            Ok(_) => {
+                if let Ok(html) = self.value.str_slice_html(self.value.selection()) {
+                    _ = clip.set_html(&html);
+                }
+                self.delete_range(self.selection())
+            }
+            Err(_) => false,
+        }
     }
 
     /// Paste from internal buffer.
@@ -407,6 +1505,38 @@ impl TextInputState {
             false
         }
     }
+
+    /// Paste the next older entry of the clipboard.
+    ///
+    /// This rotates the clipboard with [Clipboard::cycle()]. If the
+    /// text before the cursor is the current entry, as after a paste,
+    /// it is replaced with the new one. This gives an Emacs like
+    /// yank-pop with a [RingClipboard](crate::clipboard::RingClipboard).
+    /// This is recorded as a single undo step.
+    pub fn paste_cycle(&mut self) -> bool {
+        let Some(clip) = self.value.clipboard() else {
+            return false;
+        };
+        let Ok(prev) = clip.get_string() else {
+            return false;
+        };
+        if clip.cycle().is_err() {
+            return false;
+        }
+        let Ok(text) = clip.get_string() else {
+            return false;
+        };
+
+        self.value.begin_undo_seq();
+        let cursor = self.cursor();
+        let n = prev.graphemes(true).count() as upos_type;
+        if !self.has_selection() && n <= cursor && self.str_slice(cursor - n..cursor) == prev {
+            self.delete_range(cursor - n..cursor);
+        }
+        self.insert_str(text);
+        self.value.end_undo_seq();
+        true
+    }
 }
 
 impl TextInputState {
@@ -431,6 +1561,21 @@ impl TextInputState {
         self.value.undo_buffer_mut()
     }
 
+    /// Enable undo for setting/removing styles.
+    ///
+    /// This is off by default. If the styles come from a parser or
+    /// syntax highlighting, they shouldn't end up in the undo history.
+    #[inline]
+    pub fn set_undo_styles(&mut self, undo_styles: bool) {
+        self.value.set_undo_styles(undo_styles);
+    }
+
+    /// Undo for setting/removing styles.
+    #[inline]
+    pub fn undo_styles(&self) -> bool {
+        self.value.undo_styles()
+    }
+
     /// Get all recent replay recordings.
     #[inline]
     pub fn recent_replay_log(&mut self) -> Vec<UndoEntry> {
@@ -440,20 +1585,71 @@ impl TextInputState {
     /// Apply the replay recording.
     #[inline]
     pub fn replay_log(&mut self, replay: &[UndoEntry]) {
+        self.extra_cursors.clear();
         self.value.replay_log(replay)
     }
 
+    /// Apply the replay recording. Stops at the first entry that
+    /// doesn't match the current text.
+    ///
+    /// See [TextCore::replay_log_checked()]
+    #[inline]
+    pub fn replay_log_checked(&mut self, replay: &[UndoEntry]) -> Result<(), TextError> {
+        self.extra_cursors.clear();
+        self.value.replay_log_checked(replay)
+    }
+
     /// Undo operation
     #[inline]
     pub fn undo(&mut self) -> bool {
+        self.extra_cursors.clear();
         self.value.undo()
     }
 
     /// Redo operation
     #[inline]
     pub fn redo(&mut self) -> bool {
+        self.extra_cursors.clear();
         self.value.redo()
     }
+
+    /// Empty the undo buffer. The text and the replay-log
+    /// are not changed.
+    #[inline]
+    pub fn clear_undo(&mut self) {
+        if let Some(undo) = self.value.undo_buffer_mut() {
+            undo.clear_undo();
+        }
+    }
+
+    /// Number of operations that can be undone.
+    #[inline]
+    pub fn undo_len(&self) -> usize {
+        self.value
+            .undo_buffer()
+            .map(|v| v.undo_len())
+            .unwrap_or_default()
+    }
+
+    /// Number of operations that can be redone.
+    #[inline]
+    pub fn redo_len(&self) -> usize {
+        self.value
+            .undo_buffer()
+            .map(|v| v.redo_len())
+            .unwrap_or_default()
+    }
+
+    /// Run a batch of edits and record only the net change
+    /// as a single undo step.
+    ///
+    /// See [TextCore::with_undo_suspended] for the details.
+    pub fn batch_edit<R>(&mut self, f: impl FnOnce(&mut Self) -> R) -> R {
+        let suspended = self.value.suspend_undo();
+        let r = f(self);
+        self.value.resume_undo(suspended);
+        r
+    }
 }
 
 impl TextInputState {
@@ -505,6 +1701,45 @@ impl TextInputState {
         Ok(())
     }
 
+    /// Replace all ranges that use the given style-nr with
+    /// the new ranges. The ranges are grapheme ranges.
+    ///
+    /// The style-nr is reserved for this, e.g. for the underline
+    /// of a spell-checker. Each call replaces the complete set, so
+    /// no stale ranges are left behind.
+    pub fn set_underline_ranges(
+        &mut self,
+        ranges: Vec<Range<upos_type>>,
+        style: usize,
+    ) -> Result<(), TextError> {
+        self.clear_underline_ranges();
+        self.remove_all_of_style(style);
+        self.underline_style = Some(style);
+        for range in ranges {
+            self.add_range_style(range, style)?;
+        }
+        Ok(())
+    }
+
+    /// Remove the ranges set with [TextInputState::set_underline_ranges()].
+    pub fn clear_underline_ranges(&mut self) {
+        if let Some(style) = self.underline_style.take() {
+            self.remove_all_of_style(style);
+        }
+    }
+
+    /// Remove every range with the given style-nr.
+    fn remove_all_of_style(&mut self, style: usize) {
+        let ranges = if let Some(styles) = self.value.styles() {
+            styles.filter(|(_, s)| *s == style).collect::<Vec<_>>()
+        } else {
+            Vec::new()
+        };
+        for (range, style) in ranges {
+            self.value.remove_style(range, style);
+        }
+    }
+
     /// Find all styles that touch the given range.
     pub fn styles_in(&self, range: Range<usize>, buf: &mut Vec<(Range<usize>, usize)>) {
         self.value.styles_in(range, buf)
@@ -528,6 +1763,46 @@ impl TextInputState {
     pub fn styles(&self) -> Option<impl Iterator<Item = (Range<usize>, usize)> + '_> {
         self.value.styles()
     }
+
+    /// Selected text split into styled runs.
+    ///
+    /// Returns the text of each run together with the style-nrs
+    /// active for it. Empty if there is no selection.
+    pub fn selected_spans(&self) -> Vec<(String, Vec<usize>)> {
+        let selection = self.selection();
+        if selection.is_empty() {
+            return Vec::new();
+        }
+        self.style_spans(selection)
+            .map(|(range, styles)| (self.str_slice(range).into_owned(), styles))
+            .collect()
+    }
+
+    /// Styled runs for the given range.
+    ///
+    /// Yields contiguous ranges of graphemes together with the
+    /// style-nrs active for them. The style-nrs refer to the styles
+    /// set with the widget. Can be used to build a `Line` for custom
+    /// rendering.
+    pub fn style_spans(
+        &self,
+        range: Range<upos_type>,
+    ) -> impl Iterator<Item = (Range<upos_type>, Vec<usize>)> {
+        let page = self.bytes_at_range(range.clone());
+
+        let mut spans: Vec<(Range<upos_type>, Vec<usize>)> = Vec::new();
+        let mut styles = Vec::new();
+        for (pos, g) in (range.start..).zip(self.graphemes(range.clone(), range.start)) {
+            styles.clear();
+            self.value
+                .styles_at_page(page.clone(), g.text_bytes().start, &mut styles);
+            match spans.last_mut() {
+                Some((r, s)) if *s == styles => r.end = pos + 1,
+                _ => spans.push((pos..pos + 1, styles.clone())),
+            }
+        }
+        spans.into_iter()
+    }
 }
 
 impl TextInputState {
@@ -537,12 +1812,74 @@ impl TextInputState {
         self.offset
     }
 
+    /// Offset shown in display columns.
+    ///
+    /// [TextInputState::offset()] counts graphemes, this counts the
+    /// display width of the graphemes before the offset. Both differ
+    /// for wide graphemes and tabs.
+    pub fn display_offset(&self) -> upos_type {
+        self.display_columns()
+            .take(self.offset as usize + 1)
+            .last()
+            .unwrap_or_default()
+    }
+
     /// Offset shown. This is corrected if the cursor wouldn't be visible.
     #[inline]
     pub fn set_offset(&mut self, offset: upos_type) {
         self.offset = offset;
     }
 
+    /// Set cursor and offset together, e.g. to restore a saved view.
+    ///
+    /// The offset is used as given and the selection is cleared.
+    /// This bypasses the usual auto-scroll, the view is only
+    /// scrolled if the cursor would end up outside of it.
+    /// Before the first render the width is not known and the
+    /// offset is never corrected.
+    pub fn set_view(&mut self, cursor: upos_type, offset: upos_type) -> bool {
+        let old_cursor = self.cursor();
+        let old_offset = self.offset();
+
+        self.value.set_cursor(TextPosition::new(cursor, 0), false);
+        self.offset = min(offset, self.len());
+
+        let c = self.cursor();
+        let w = (self.inner.width + self.dark_offset.0) as upos_type;
+        if w > 0 && (c < self.offset || c >= self.offset + w) {
+            self.scroll_cursor_to_visible();
+        }
+
+        old_cursor != self.cursor() || old_offset != self.offset()
+    }
+
+    /// Columns of context kept visible left and right of the cursor
+    /// when scrolling. This is limited to half the width of the widget.
+    /// Default is 0.
+    #[inline]
+    pub fn set_scroll_off(&mut self, scroll_off: u16) {
+        self.scroll_off = scroll_off;
+    }
+
+    /// Columns of context kept visible left and right of the cursor.
+    #[inline]
+    pub fn scroll_off(&self) -> u16 {
+        self.scroll_off
+    }
+
+    /// Number of graphemes scrolled by Alt+Left/Right and
+    /// the horizontal mouse wheel. Default is 1.
+    #[inline]
+    pub fn set_scroll_step(&mut self, step: upos_type) {
+        self.scroll_step = step;
+    }
+
+    /// Number of graphemes scrolled by Alt+Left/Right and the mouse wheel.
+    #[inline]
+    pub fn scroll_step(&self) -> upos_type {
+        self.scroll_step
+    }
+
     /// Cursor position.
     #[inline]
     pub fn cursor(&self) -> upos_type {
@@ -562,6 +1899,19 @@ impl TextInputState {
             .set_cursor(TextPosition::new(cursor, 0), extend_selection)
     }
 
+    /// Set the cursor position.
+    /// Returns an error if the position is after the end of the
+    /// text, [TextInputState::set_cursor()] silently caps it.
+    #[inline]
+    pub fn try_set_cursor(
+        &mut self,
+        cursor: upos_type,
+        extend_selection: bool,
+    ) -> Result<bool, TextError> {
+        self.value
+            .try_set_cursor(TextPosition::new(cursor, 0), extend_selection)
+    }
+
     /// Selection.
     #[inline]
     pub fn has_selection(&self) -> bool {
@@ -575,6 +1925,18 @@ impl TextInputState {
         v.start.x..v.end.x
     }
 
+    /// Direction of the selection.
+    ///
+    /// Compares the anchor with the cursor. Returns `Less` if the
+    /// cursor is at the end of the selection, `Greater` if the cursor
+    /// is at the start, and None if there is no selection.
+    pub fn selection_direction(&self) -> Option<Ordering> {
+        match self.anchor().cmp(&self.cursor()) {
+            Ordering::Equal => None,
+            v => Some(v),
+        }
+    }
+
     /// Selection.
     #[inline]
     pub fn set_selection(&mut self, anchor: upos_type, cursor: upos_type) -> bool {
@@ -582,21 +1944,59 @@ impl TextInputState {
             .set_selection(TextPosition::new(anchor, 0), TextPosition::new(cursor, 0))
     }
 
+    /// Set the selection using a byte range.
+    ///
+    /// If the range starts or ends inside a grapheme, the selection
+    /// is widened to include the whole grapheme. The anchor is set
+    /// to the start, the cursor to the end of the range.
+    #[inline]
+    pub fn set_selection_bytes(&mut self, range: Range<usize>) -> Result<bool, TextError> {
+        let anchor = self.try_byte_pos(range.start)?;
+        let mut cursor = self.try_byte_pos(range.end)?;
+        if self.try_byte_at(cursor)?.start < range.end {
+            cursor += 1;
+        }
+        Ok(self.set_selection(anchor, cursor))
+    }
+
+    /// Selection as byte range.
+    #[inline]
+    pub fn selection_bytes(&self) -> Range<usize> {
+        self.bytes_at_range(self.selection())
+    }
+
     /// Selection.
     #[inline]
     pub fn select_all(&mut self) -> bool {
         self.value.select_all()
     }
 
-    /// Selection.
+    /// Select the line. For a single line this is the same
+    /// as [TextInputState::select_all()].
     #[inline]
-    pub fn selected_text(&self) -> &str {
-        match self.str_slice(self.selection()) {
-            Cow::Borrowed(v) => v,
-            Cow::Owned(_) => {
-                unreachable!()
+    pub fn select_line(&mut self) -> bool {
+        self.select_all()
+    }
+
+    /// Select from the first to the last non-whitespace grapheme.
+    /// If there is only whitespace, the selection is cleared and
+    /// the cursor set to the start.
+    pub fn select_trimmed(&mut self) -> bool {
+        let mut start = None;
+        let mut end = 0;
+        for (pos, g) in (0..).zip(self.text_graphemes(0)) {
+            if !g.is_whitespace() {
+                start.get_or_insert(pos);
+                end = pos + 1;
             }
         }
+        self.set_selection(start.unwrap_or_default(), end)
+    }
+
+    /// Selection.
+    #[inline]
+    pub fn selected_text(&self) -> Cow<'_, str> {
+        self.str_slice(self.selection())
     }
 }
 
@@ -613,6 +2013,42 @@ impl TextInputState {
         self.value.text().as_str()
     }
 
+    /// The text-store, for the store specific functions.
+    #[inline]
+    pub fn value_store(&self) -> &TextString {
+        self.value.text()
+    }
+
+    /// Change the text-store directly.
+    ///
+    /// The changes bypass the widget, so afterwards the store is
+    /// treated like a new text with [TextInputState::set_text()]:
+    /// line-breaks are replaced and control characters removed,
+    /// the cursor and anchor are capped to the new length, and the
+    /// styles and the undo buffer are cleared.
+    pub fn with_value_store_mut<R>(&mut self, f: impl FnOnce(&mut TextString) -> R) -> R {
+        let mut store = self.value.take_text();
+        let r = f(&mut store);
+        if store.as_str().contains(is_invalid_char) {
+            store = TextString::new_string(sanitize_text(store.as_str().to_string()));
+        }
+        self.value.set_text(store);
+        self.scroll_cursor_to_visible();
+        r
+    }
+
+    /// Text with the newline markers replaced by line-breaks.
+    ///
+    /// See [TextInputState::set_newline_marker()].
+    pub fn text_expanded(&self) -> Cow<'_, str> {
+        match self.newline_marker {
+            Some(marker) if self.text().contains(marker) => {
+                Cow::Owned(self.text().replace(marker, "\n"))
+            }
+            _ => Cow::Borrowed(self.text()),
+        }
+    }
+
     /// Text slice as `Cow<str>`. Uses a byte range.
     #[inline]
     pub fn str_slice_byte(&self, range: Range<usize>) -> Cow<'_, str> {
@@ -640,18 +2076,48 @@ impl TextInputState {
             .str_slice(TextRange::new((range.start, 0), (range.end, 0)))
     }
 
+    /// Text before the cursor.
+    #[inline]
+    pub fn text_before_cursor(&self) -> Cow<'_, str> {
+        self.str_slice(0..self.cursor())
+    }
+
+    /// Text after the cursor.
+    #[inline]
+    pub fn text_after_cursor(&self) -> Cow<'_, str> {
+        self.str_slice(self.cursor()..self.len())
+    }
+
     /// Length as grapheme count.
     #[inline]
     pub fn len(&self) -> upos_type {
         self.value.line_width(0).expect("valid_row")
     }
 
+    /// Length in bytes. This is O(1).
+    #[inline]
+    pub fn len_bytes(&self) -> usize {
+        self.value.len_bytes()
+    }
+
+    /// Length in chars. This is O(1).
+    #[inline]
+    pub fn len_chars(&self) -> usize {
+        self.value.len_chars()
+    }
+
     /// Length as grapheme count.
     #[inline]
     pub fn line_width(&self) -> upos_type {
         self.value.line_width(0).expect("valid_row")
     }
 
+    /// Statistics for the text.
+    #[inline]
+    pub fn stats(&self) -> TextStats {
+        self.value.stats()
+    }
+
     /// Iterator for the glyphs of the lines in range.
     /// Glyphs here a grapheme + display length.
     #[inline]
@@ -661,6 +2127,95 @@ impl TextInputState {
             .expect("valid_rows")
     }
 
+    /// Iterator for the glyphs with a different tab-width and
+    /// show_ctrl than the one used for editing.
+    ///
+    /// This can be used to render a preview of the text.
+    #[inline]
+    pub fn glyphs_with(
+        &self,
+        screen_offset: u16,
+        screen_width: u16,
+        tabs: u16,
+        show_ctrl: bool,
+    ) -> impl Iterator<Item = Glyph<'_>> {
+        self.value
+            .glyphs_with(0..1, screen_offset, screen_width, tabs, show_ctrl)
+            .expect("valid_rows")
+    }
+
+    /// Display width of the whole text.
+    ///
+    /// This differs from [TextInputState::len()] for wide graphemes
+    /// and tabs. The result saturates at u16::MAX for very long texts.
+    pub fn total_display_width(&self) -> u16 {
+        let width = self.display_columns().last().unwrap_or_default();
+        min(width, u16::MAX as upos_type) as u16
+    }
+
+    /// Width needed to show the whole text and the caret after it,
+    /// limited to max.
+    ///
+    /// This can be used to size the widget before rendering.
+    /// Borders and padding are not included.
+    pub fn preferred_width(&self, max: u16) -> u16 {
+        min(self.total_display_width().saturating_add(1), max)
+    }
+
+    /// Display column where each grapheme starts, followed by the
+    /// total display width. Uses the glyph widths as rendered.
+    fn display_columns(&self) -> impl Iterator<Item = upos_type> + '_ {
+        self.glyphs(0, u16::MAX)
+            .map(|g| g.screen_width() as upos_type)
+            .chain(once(0))
+            .scan(0, |width: &mut upos_type, w| {
+                let start = *width;
+                *width = width.saturating_add(w);
+                Some(start)
+            })
+    }
+
+    /// Text truncated to fit max_width display columns.
+    ///
+    /// If the text doesn't fit, the ellipsis is appended and the
+    /// result including the ellipsis fits max_width, unless the
+    /// ellipsis alone is wider. Wide graphemes at the boundary are
+    /// not split.
+    pub fn display_truncated(&self, max_width: u16, ellipsis: &str) -> String {
+        let text = self.text();
+        let ellipsis_width = unicode_display_width::width(ellipsis) as u16;
+        let budget = max_width.saturating_sub(ellipsis_width);
+
+        let mut fit = 0;
+        for g in self.glyphs(0, u16::MAX) {
+            let end = g.screen_pos().0 + g.screen_width();
+            if end > max_width {
+                let mut buf = String::with_capacity(fit + ellipsis.len());
+                buf.push_str(&text[..fit]);
+                buf.push_str(ellipsis);
+                return buf;
+            }
+            if end <= budget {
+                fit = g.text_bytes().end;
+            }
+        }
+        text.to_string()
+    }
+
+    /// Grapheme before the cursor.
+    /// Returns None at the start of the text.
+    pub fn char_before_cursor(&self) -> Option<&str> {
+        let g = self.text_graphemes(self.cursor()).prev()?;
+        Some(&self.text()[g.text_bytes()])
+    }
+
+    /// Grapheme after the cursor.
+    /// Returns None at the end of the text.
+    pub fn char_after_cursor(&self) -> Option<&str> {
+        let g = self.text_graphemes(self.cursor()).next()?;
+        Some(&self.text()[g.text_bytes()])
+    }
+
     /// Get a cursor over all the text with the current position set at pos.
     #[inline]
     pub fn text_graphemes(&self, pos: upos_type) -> impl Cursor<Item = Grapheme<'_>> {
@@ -769,76 +2324,452 @@ impl TextInputState {
 
 impl TextInputState {
     /// Reset to empty.
+    ///
+    /// This is recorded as a single undo step.
+    /// It is not bound to a key by default.
     #[inline]
     pub fn clear(&mut self) -> bool {
         if self.is_empty() {
             false
         } else {
             self.offset = 0;
-            self.value.clear();
+            self.extra_cursors.clear();
+            self.value
+                .remove_str_range(TextRange::new((0, 0), (self.len(), 0)))
+                .expect("valid_range");
             true
         }
     }
 
+    /// Return to a fresh state.
+    ///
+    /// Other than [TextInputState::clear()] this also resets the
+    /// selection, offset, invalid flag, styles and the undo buffer,
+    /// and marks the empty text as clean. The configuration and the
+    /// focus are kept, so the field can be reused.
+    pub fn reset(&mut self) {
+        self.offset = 0;
+        self.dark_offset = (0, 0);
+        self.value.set_text(TextString::new());
+        self.clean_text.clear();
+        self.invalid = false;
+        self.invalid_reason = None;
+        self.underline_style = None;
+        self.preedit = None;
+        self.preedit_cursor = 0;
+        self.extra_cursors.clear();
+        self.drag_overflow = None;
+    }
+
     /// Set text.
     ///
-    /// Returns an error if the text contains line-breaks.
+    /// Line-breaks are replaced with a space, other control characters
+    /// are removed. Use [TextInputState::try_set_text()] if you want
+    /// an error instead.
     #[inline]
     pub fn set_text<S: Into<String>>(&mut self, s: S) {
         self.offset = 0;
-        self.value.set_text(TextString::new_string(s.into()));
+        self.extra_cursors.clear();
+        self.value
+            .set_text(TextString::new_string(sanitize_text(s.into())));
+    }
+
+    /// Set text and styles at once. This clears the undo buffer
+    /// like [TextInputState::set_text()], the styles don't add
+    /// an undo entry.
+    ///
+    /// The styles are byte ranges into the text after line-breaks
+    /// and control characters have been replaced.
+    pub fn set_text_with_styles<S: Into<String>>(
+        &mut self,
+        s: S,
+        styles: Vec<(Range<usize>, usize)>,
+    ) {
+        self.offset = 0;
+        self.extra_cursors.clear();
+        self.value
+            .set_text_with_styles(TextString::new_string(sanitize_text(s.into())), styles);
+    }
+
+    /// Set text, but keep cursor, selection and offset.
+    ///
+    /// They are clamped to the new text, otherwise this works
+    /// like [TextInputState::set_text()]. Useful for programmatic
+    /// updates of the value that shouldn't jump the view to the start.
+    pub fn set_text_keep_cursor<S: Into<String>>(&mut self, s: S) {
+        let offset = self.offset;
+        self.extra_cursors.clear();
+        self.value
+            .set_text(TextString::new_string(sanitize_text(s.into())));
+        self.offset = min(offset, self.len());
+        self.scroll_cursor_to_visible();
+    }
+
+    /// Set text.
+    ///
+    /// Returns an error if the text contains line-breaks or
+    /// other control characters.
+    #[inline]
+    pub fn try_set_text<S: Into<String>>(&mut self, s: S) -> Result<(), TextError> {
+        let s = s.into();
+        if s.contains(is_invalid_char) {
+            return Err(TextError::InvalidText(s));
+        }
+        self.offset = 0;
+        self.extra_cursors.clear();
+        self.value.set_text(TextString::new_string(s));
+        Ok(())
     }
 
     /// Insert a char at the current position.
     #[inline]
     pub fn insert_char(&mut self, c: char) -> bool {
+        self.try_insert_char(c).expect("valid_cursor")
+    }
+
+    /// Insert a char at the current position.
+    #[inline]
+    pub fn try_insert_char(&mut self, c: char) -> Result<bool, TextError> {
+        self.try_insert_char_result(c).map(|v| v.inserted > 0)
+    }
+
+    /// Insert a char at the current position.
+    /// Returns the number of inserted and rejected chars.
+    #[inline]
+    pub fn insert_char_result(&mut self, c: char) -> InsertResult {
+        self.try_insert_char_result(c).expect("valid_cursor")
+    }
+
+    /// Insert a char at the current position.
+    /// Returns the number of inserted and rejected chars.
+    pub fn try_insert_char_result(&mut self, c: char) -> Result<InsertResult, TextError> {
+        let c = if let Some(remap) = self.char_remap {
+            let Some(c) = remap(c) else {
+                self.reject();
+                return Ok(InsertResult {
+                    inserted: 0,
+                    rejected: 1,
+                });
+            };
+            c
+        } else {
+            c
+        };
+        let c = if c == '\n' && self.enter_inserts_space {
+            ' '
+        } else {
+            c
+        };
+
+        // no line-breaks in a single line.
+        if c == '\n' {
+            self.reject();
+            return Ok(InsertResult {
+                inserted: 0,
+                rejected: 1,
+            });
+        }
+        if is_zero_width(c) && self.rejects_combining(self.selection().start) {
+            self.reject();
+            return Ok(InsertResult {
+                inserted: 0,
+                rejected: 1,
+            });
+        }
+        if c == ' ' && self.after_space(self.selection().start) {
+            self.reject();
+            return Ok(InsertResult {
+                inserted: 0,
+                rejected: 1,
+            });
+        }
+        let bytes = if c == '\t' {
+            self.tab_bytes(self.selection().start)
+        } else {
+            c.len_utf8() * (self.extra_cursors.len() + 1)
+        };
+        if bytes > self.byte_budget() {
+            self.reject();
+            return Ok(InsertResult {
+                inserted: 0,
+                rejected: 1,
+            });
+        }
+
+        if self.has_selection() || c == '\t' {
+            self.extra_cursors.clear();
+        }
         if self.has_selection() {
-            self.value
-                .remove_str_range(self.value.selection())
-                .expect("valid_selection");
+            self.value.remove_str_range(self.value.selection())?;
         }
-        if c == '\n' {
-            return false;
-        } else if c == '\t' {
-            self.value
-                .insert_tab(self.value.cursor())
-                .expect("valid_cursor");
+        if c == '\t' {
+            self.value.insert_tab(self.value.cursor())?;
+        } else if !self.extra_cursors.is_empty() {
+            self.insert_char_multi(c)?;
         } else {
-            self.value
-                .insert_char(self.value.cursor(), c)
-                .expect("valid_cursor");
+            self.value.insert_char(self.value.cursor(), c)?;
         }
         self.scroll_cursor_to_visible();
-        true
+        Ok(InsertResult {
+            inserted: 1,
+            rejected: 0,
+        })
     }
 
     /// Insert a tab character at the cursor position.
     /// Removes the selection and inserts the tab.
+    #[inline]
     pub fn insert_tab(&mut self) -> bool {
+        self.try_insert_tab().expect("valid_cursor")
+    }
+
+    /// Insert a tab character at the cursor position.
+    /// Removes the selection and inserts the tab.
+    pub fn try_insert_tab(&mut self) -> Result<bool, TextError> {
+        if self.tab_bytes(self.selection().start) > self.byte_budget() {
+            self.reject();
+            return Ok(false);
+        }
+        self.extra_cursors.clear();
         if self.has_selection() {
-            self.value
-                .remove_str_range(self.value.selection())
-                .expect("valid_selection");
+            self.value.remove_str_range(self.value.selection())?;
         }
-        self.value
-            .insert_tab(self.value.cursor())
-            .expect("valid_cursor");
+        self.value.insert_tab(self.value.cursor())?;
         self.scroll_cursor_to_visible();
-        true
+        Ok(true)
     }
 
     /// Insert a str at the current position.
     #[inline]
     pub fn insert_str(&mut self, t: impl AsRef<str>) -> bool {
+        self.try_insert_str(t).expect("valid_cursor")
+    }
+
+    /// Insert a str at the current position.
+    #[inline]
+    pub fn try_insert_str(&mut self, t: impl AsRef<str>) -> Result<bool, TextError> {
+        self.try_insert_str_result(t).map(|_| true)
+    }
+
+    /// Insert a str at the current position.
+    /// Returns the number of inserted and rejected chars.
+    #[inline]
+    pub fn insert_str_result(&mut self, t: impl AsRef<str>) -> InsertResult {
+        self.try_insert_str_result(t).expect("valid_cursor")
+    }
+
+    /// Insert a str at the current position.
+    /// Returns the number of inserted and rejected chars.
+    pub fn try_insert_str_result(&mut self, t: impl AsRef<str>) -> Result<InsertResult, TextError> {
         let t = t.as_ref();
+        let (t, rejected) = if self.newline_marker.is_none() && self.truncate_newline {
+            let v = t.split(['\n', '\r']).next().unwrap_or_default();
+            (v, t[v.len()..].chars().count())
+        } else {
+            (t, 0)
+        };
+        // same rules as set_text, removed control chars are rejected.
+        let mut buf = [0u8; 4];
+        let newline = match self.newline_marker {
+            Some(marker) => &*marker.encode_utf8(&mut buf),
+            None => " ",
+        };
+        let removed = t
+            .chars()
+            .filter(|c| is_invalid_char(*c) && *c != '\n' && *c != '\r')
+            .count();
+        let t = sanitize_str(t, newline);
+        let (t, rejected) = (t.as_ref(), rejected + removed);
+        let (t, rejected) = if self.rejects_combining(self.selection().start) {
+            let v = t.trim_start_matches(is_zero_width);
+            (v, rejected + t[..t.len() - v.len()].chars().count())
+        } else {
+            (t, rejected)
+        };
+        let collapsed;
+        let (t, rejected) = if self.collapse_spaces {
+            let mut v = String::with_capacity(t.len());
+            let mut prev_space = self.after_space(self.selection().start);
+            for c in t.chars() {
+                if c == ' ' && prev_space {
+                    continue;
+                }
+                prev_space = c == ' ';
+                v.push(c);
+            }
+            collapsed = v;
+            (
+                collapsed.as_str(),
+                rejected + t.chars().count() - collapsed.chars().count(),
+            )
+        } else {
+            (t, rejected)
+        };
+        let (t, rejected) = if t.len() > self.byte_budget() {
+            let budget = self.byte_budget();
+            let fit = t
+                .grapheme_indices(true)
+                .map(|(idx, g)| idx + g.len())
+                .take_while(|end| *end <= budget)
+                .last()
+                .unwrap_or_default();
+            (&t[..fit], rejected + t[fit..].chars().count())
+        } else {
+            (t, rejected)
+        };
+        if rejected > 0 {
+            self.reject();
+        }
+
+        self.extra_cursors.clear();
         if self.has_selection() {
+            self.value.remove_str_range(self.value.selection())?;
+        }
+        self.value.insert_str(self.value.cursor(), t)?;
+        self.scroll_cursor_to_visible();
+        // don't leave the view scrolled past a shorter text.
+        let w = (self.inner.width + self.dark_offset.0) as upos_type;
+        self.set_offset(min(self.offset(), (self.len() + 1).saturating_sub(w)));
+
+        Ok(InsertResult {
+            inserted: t.chars().count(),
+            rejected,
+        })
+    }
+
+    /// Insert pre-segmented graphemes at the current position.
+    /// Replaces the selection.
+    ///
+    /// Each item must be exactly one grapheme cluster without
+    /// control characters. This is recorded as a single undo step.
+    ///
+    /// Returns the number of inserted graphemes.
+    #[inline]
+    pub fn insert_graphemes<'b>(&mut self, graphemes: impl IntoIterator<Item = &'b str>) -> usize {
+        self.try_insert_graphemes(graphemes)
+            .expect("valid_graphemes")
+    }
+
+    /// Insert pre-segmented graphemes at the current position.
+    /// Replaces the selection.
+    ///
+    /// Each item must be exactly one grapheme cluster without
+    /// control characters. This is recorded as a single undo step.
+    ///
+    /// Returns the number of inserted graphemes.
+    pub fn try_insert_graphemes<'b>(
+        &mut self,
+        graphemes: impl IntoIterator<Item = &'b str>,
+    ) -> Result<usize, TextError> {
+        let graphemes = graphemes.into_iter().collect::<Vec<_>>();
+        if let Some(g) = graphemes
+            .iter()
+            .find(|g| g.contains(is_invalid_char) || g.graphemes(true).count() != 1)
+        {
+            self.reject();
+            return Err(TextError::InvalidText(g.to_string()));
+        }
+        let mut budget = self.byte_budget();
+        let fit = graphemes
+            .iter()
+            .take_while(|g| {
+                if g.len() <= budget {
+                    budget -= g.len();
+                    true
+                } else {
+                    false
+                }
+            })
+            .count();
+        let graphemes = if fit < graphemes.len() {
+            self.reject();
+            &graphemes[..fit]
+        } else {
+            &graphemes[..]
+        };
+
+        self.extra_cursors.clear();
+        self.value.begin_undo_seq();
+        let r = if self.has_selection() {
+            self.value.remove_str_range(self.value.selection())
+        } else {
+            Ok(true)
+        }
+        .and_then(|_| {
             self.value
-                .remove_str_range(self.value.selection())
-                .expect("valid_selection");
+                .insert_graphemes(self.value.cursor(), graphemes.iter().copied())
+        });
+        self.value.end_undo_seq();
+
+        let n = r?;
+        self.scroll_cursor_to_visible();
+        Ok(n)
+    }
+
+    /// Insert a str with styles at the current position.
+    /// Replaces the selection.
+    ///
+    /// The style ranges are byte-ranges relative to the inserted
+    /// str, they are moved to the insertion point and clipped to
+    /// the inserted text. Line-breaks should be removed beforehand,
+    /// their replacement can shift the styles.
+    /// This is recorded as a single undo step.
+    #[inline]
+    pub fn insert_styled(&mut self, t: &str, styles: &[(Range<usize>, usize)]) -> bool {
+        self.try_insert_styled(t, styles).expect("valid_cursor")
+    }
+
+    /// Insert a str with styles at the current position.
+    /// Replaces the selection.
+    ///
+    /// The style ranges are byte-ranges relative to the inserted
+    /// str, they are moved to the insertion point and clipped to
+    /// the inserted text. Line-breaks should be removed beforehand,
+    /// their replacement can shift the styles.
+    /// This is recorded as a single undo step.
+    pub fn try_insert_styled(
+        &mut self,
+        t: &str,
+        styles: &[(Range<usize>, usize)],
+    ) -> Result<bool, TextError> {
+        self.value.begin_undo_seq();
+        let r = self.try_byte_at(self.selection().start).and_then(|v| {
+            let start = v.start;
+            self.try_insert_str_result(t)?;
+            let len = self.try_byte_at(self.cursor())?.start - start;
+            for (range, style) in styles {
+                let range = start + min(range.start, len)..start + min(range.end, len);
+                if !range.is_empty() {
+                    self.value.add_style(range, *style);
+                }
+            }
+            Ok(true)
+        });
+        self.value.end_undo_seq();
+        r
+    }
+
+    /// Remove leading and trailing whitespace.
+    /// This is recorded as a single undo step.
+    pub fn trim(&mut self) -> bool {
+        let text = self.text();
+        let len = text.len();
+        let end = text.trim_end().len();
+        let start = min(len - text.trim_start().len(), end);
+
+        if start == 0 && end == len {
+            return false;
         }
-        self.value
-            .insert_str(self.value.cursor(), t)
-            .expect("valid_cursor");
+
+        let trailing = self.value.byte_range(end..len).expect("valid_range");
+        let leading = self.value.byte_range(0..start).expect("valid_range");
+
+        self.extra_cursors.clear();
+        self.value.begin_undo_seq();
+        self.value.remove_str_range(trailing).expect("valid_range");
+        self.value.remove_str_range(leading).expect("valid_range");
+        self.value.end_undo_seq();
+
         self.scroll_cursor_to_visible();
         true
     }
@@ -853,6 +2784,7 @@ impl TextInputState {
     #[inline]
     pub fn try_delete_range(&mut self, range: Range<upos_type>) -> Result<bool, TextError> {
         if !range.is_empty() {
+            self.extra_cursors.clear();
             self.value
                 .remove_str_range(TextRange::new((range.start, 0), (range.end, 0)))?;
             self.scroll_cursor_to_visible();
@@ -861,38 +2793,186 @@ impl TextInputState {
             Ok(false)
         }
     }
+
+    /// Insert a char at the given grapheme position.
+    ///
+    /// The cursor stays where it is, it is only moved with the text
+    /// if it is at or after the position.
+    #[inline]
+    pub fn insert_char_at(&mut self, pos: upos_type, c: char) -> bool {
+        self.try_insert_char_at(pos, c).expect("valid_pos")
+    }
+
+    /// Insert a char at the given grapheme position.
+    ///
+    /// The cursor stays where it is, it is only moved with the text
+    /// if it is at or after the position. Control characters other
+    /// than tab are an error.
+    ///
+    /// The char goes through the same remap and checks as
+    /// [TextInputState::insert_char()], and is rejected if it
+    /// doesn't pass.
+    pub fn try_insert_char_at(&mut self, pos: upos_type, c: char) -> Result<bool, TextError> {
+        let c = if let Some(remap) = self.char_remap {
+            let Some(c) = remap(c) else {
+                self.reject();
+                return Ok(false);
+            };
+            c
+        } else {
+            c
+        };
+        let c = if c == '\n' && self.enter_inserts_space {
+            ' '
+        } else {
+            c
+        };
+        if is_invalid_char(c) {
+            return Err(TextError::InvalidText(c.to_string()));
+        }
+        if pos > self.len() {
+            return Err(TextError::TextPositionOutOfBounds(TextPosition::new(
+                pos, 0,
+            )));
+        }
+
+        let bytes = if c == '\t' {
+            self.tab_bytes(pos)
+        } else {
+            c.len_utf8()
+        };
+        let budget = if let Some(max_bytes) = self.max_bytes {
+            max_bytes.saturating_sub(self.len_bytes())
+        } else {
+            usize::MAX
+        };
+        if (is_zero_width(c) && self.rejects_combining(pos))
+            || (c == ' ' && self.after_space(pos))
+            || bytes > budget
+        {
+            self.reject();
+            return Ok(false);
+        }
+
+        self.extra_cursors.clear();
+        if c == '\t' {
+            self.value.insert_tab(TextPosition::new(pos, 0))
+        } else {
+            self.value.insert_char(TextPosition::new(pos, 0), c)
+        }
+    }
+
+    /// Remove the grapheme at the given position.
+    ///
+    /// The cursor stays where it is, it is only moved with the text
+    /// if it is after the position.
+    #[inline]
+    pub fn remove_grapheme_at(&mut self, pos: upos_type) -> bool {
+        self.try_remove_grapheme_at(pos).expect("valid_pos")
+    }
+
+    /// Remove the grapheme at the given position.
+    ///
+    /// The cursor stays where it is, it is only moved with the text
+    /// if it is after the position.
+    pub fn try_remove_grapheme_at(&mut self, pos: upos_type) -> Result<bool, TextError> {
+        self.extra_cursors.clear();
+        self.value.remove_next_char(TextPosition::new(pos, 0))
+    }
+
+    /// Deletes the given byte range.
+    ///
+    /// Returns an error if the range doesn't start and end at
+    /// a grapheme boundary.
+    pub fn delete_byte_range(&mut self, bytes: Range<usize>) -> Result<bool, TextError> {
+        let range = self.value.byte_range(bytes.clone())?;
+        if self.value.bytes_at_range(range)? != bytes {
+            return Err(TextError::ByteRangeNotCharBoundary(
+                Some(bytes.start),
+                Some(bytes.end),
+            ));
+        }
+        self.try_delete_range(range.start.x..range.end.x)
+    }
 }
 
 impl TextInputState {
     /// Delete the char after the cursor.
+    ///
+    /// Returns [TextOutcome::TextChanged] if something was deleted,
+    /// [TextOutcome::Changed] if it only scrolled and
+    /// [TextOutcome::Unchanged] at the end of the text.
     #[inline]
-    pub fn delete_next_char(&mut self) -> bool {
+    pub fn delete_next_char(&mut self) -> TextOutcome {
+        self.try_delete_next_char().expect("valid_cursor")
+    }
+
+    /// Delete the char after the cursor.
+    ///
+    /// Returns [TextOutcome::TextChanged] if something was deleted,
+    /// [TextOutcome::Changed] if it only scrolled and
+    /// [TextOutcome::Unchanged] at the end of the text.
+    pub fn try_delete_next_char(&mut self) -> Result<TextOutcome, TextError> {
         if self.has_selection() {
-            self.delete_range(self.selection())
+            let r = self.try_delete_range(self.selection())?;
+            Ok(TextOutcome::edit(r, false))
         } else {
-            let r = self
-                .value
-                .remove_next_char(self.value.cursor())
-                .expect("valid_cursor");
+            self.extra_cursors.clear();
+            let r = self.value.remove_next_char(self.value.cursor())?;
             let s = self.scroll_cursor_to_visible();
 
-            r || s
+            Ok(TextOutcome::edit(r, s))
+        }
+    }
+
+    /// Spaces before the cursor that fill a tab column.
+    /// Only with smart_backspace.
+    fn tab_column_before_cursor(&self) -> Option<Range<upos_type>> {
+        let c = self.cursor();
+        let tabs = self.value.tab_width() as upos_type;
+        if !self.smart_backspace || c == 0 || tabs <= 1 {
+            return None;
+        }
+        let start = (c - 1) / tabs * tabs;
+        let bytes = self.try_bytes_at_range(start..c).ok()?;
+        if self.text()[bytes].bytes().all(|v| v == b' ') {
+            Some(start..c)
+        } else {
+            None
         }
     }
 
     /// Delete the char before the cursor.
-    #[inline]
-    pub fn delete_prev_char(&mut self) -> bool {
-        if self.value.has_selection() {
-            self.delete_range(self.selection())
+    ///
+    /// Returns [TextOutcome::TextChanged] if something was deleted,
+    /// [TextOutcome::Changed] if it only scrolled and
+    /// [TextOutcome::Unchanged] at the start of the text.
+    #[inline]
+    pub fn delete_prev_char(&mut self) -> TextOutcome {
+        self.try_delete_prev_char().expect("valid_cursor")
+    }
+
+    /// Delete the char before the cursor.
+    ///
+    /// Returns [TextOutcome::TextChanged] if something was deleted,
+    /// [TextOutcome::Changed] if it only scrolled and
+    /// [TextOutcome::Unchanged] at the start of the text.
+    pub fn try_delete_prev_char(&mut self) -> Result<TextOutcome, TextError> {
+        let range = if self.value.has_selection() {
+            Some(self.selection())
         } else {
-            let r = self
-                .value
-                .remove_prev_char(self.value.cursor())
-                .expect("valid_cursor");
+            self.tab_column_before_cursor()
+        };
+
+        if let Some(range) = range {
+            let r = self.try_delete_range(range)?;
+            Ok(TextOutcome::edit(r, false))
+        } else {
+            self.extra_cursors.clear();
+            let r = self.value.remove_prev_char(self.value.cursor())?;
             let s = self.scroll_cursor_to_visible();
 
-            r || s
+            Ok(TextOutcome::edit(r, s))
         }
     }
 
@@ -964,6 +3044,13 @@ impl TextInputState {
         self.value.is_word_boundary(TextPosition::new(pos, 0))
     }
 
+    /// Is the cursor at a word boundary?
+    pub fn cursor_at_word_boundary(&self) -> bool {
+        self.value
+            .is_word_boundary(self.value.cursor())
+            .expect("valid_cursor")
+    }
+
     /// Find the start of the word at pos.
     pub fn word_start(&self, pos: upos_type) -> upos_type {
         self.try_word_start(pos).expect("valid_pos")
@@ -986,46 +3073,247 @@ impl TextInputState {
         self.value.word_end(TextPosition::new(pos, 0)).map(|v| v.x)
     }
 
+    /// The word the cursor is in or directly before/after.
+    ///
+    /// Returns None if there is whitespace on both sides
+    /// of the cursor.
+    pub fn word_at_cursor(&self) -> Option<(Range<upos_type>, &str)> {
+        let cursor = self.cursor();
+        let start = self.word_start(cursor);
+        let end = self.word_end(cursor);
+        if start == end {
+            None
+        } else {
+            let bytes = self.bytes_at_range(start..end);
+            Some((start..end, &self.text()[bytes]))
+        }
+    }
+
+    /// Find the next occurrence of needle at or after from.
+    ///
+    /// Matches whole graphemes and returns the grapheme range.
+    pub fn find(&self, needle: &str, from: upos_type) -> Option<Range<upos_type>> {
+        self.search(needle, from, false, false)
+    }
+
+    /// Find the previous occurrence of needle that ends at or
+    /// before the given position.
+    ///
+    /// Matches whole graphemes and returns the grapheme range.
+    pub fn rfind(&self, needle: &str, before: upos_type) -> Option<Range<upos_type>> {
+        self.search(needle, before, true, false)
+    }
+
+    /// Like [TextInputState::find()] but ignores the case.
+    pub fn find_ignore_case(&self, needle: &str, from: upos_type) -> Option<Range<upos_type>> {
+        self.search(needle, from, false, true)
+    }
+
+    /// Like [TextInputState::rfind()] but ignores the case.
+    pub fn rfind_ignore_case(&self, needle: &str, before: upos_type) -> Option<Range<upos_type>> {
+        self.search(needle, before, true, true)
+    }
+
+    fn search(
+        &self,
+        needle: &str,
+        pos: upos_type,
+        reverse: bool,
+        ignore_case: bool,
+    ) -> Option<Range<upos_type>> {
+        let text = self.text().graphemes(true).collect::<Vec<_>>();
+        let needle = needle.graphemes(true).collect::<Vec<_>>();
+        if needle.is_empty() || needle.len() > text.len() {
+            return None;
+        }
+        let matches_at = |i: usize| {
+            text[i..i + needle.len()]
+                .iter()
+                .zip(needle.iter())
+                .all(|(t, n)| {
+                    if ignore_case {
+                        t.to_lowercase() == n.to_lowercase()
+                    } else {
+                        t == n
+                    }
+                })
+        };
+
+        let last = text.len() - needle.len();
+        let pos = pos as usize;
+        let found = if reverse {
+            let start = pos.checked_sub(needle.len())?;
+            (0..=min(start, last)).rev().find(|i| matches_at(*i))
+        } else {
+            (pos..=last).find(|i| matches_at(*i))
+        };
+        found.map(|i| i as upos_type..(i + needle.len()) as upos_type)
+    }
+
     /// Deletes the next word.
     #[inline]
     pub fn delete_next_word(&mut self) -> bool {
+        self.try_delete_next_word().expect("valid_cursor")
+    }
+
+    /// Deletes the next word.
+    pub fn try_delete_next_word(&mut self) -> Result<bool, TextError> {
         if self.has_selection() {
-            self.delete_range(self.selection())
+            self.try_delete_range(self.selection())
         } else {
             let cursor = self.cursor();
 
-            let start = self.next_word_start(cursor);
+            let start = self.try_next_word_start(cursor)?;
             if start != cursor {
-                self.delete_range(cursor..start)
+                self.try_delete_range(cursor..start)
             } else {
-                let end = self.next_word_end(cursor);
-                self.delete_range(cursor..end)
+                let end = self.try_next_word_end(cursor)?;
+                self.try_delete_range(cursor..end)
             }
         }
     }
 
-    /// Deletes the given range.
+    /// Deletes the previous word.
     #[inline]
     pub fn delete_prev_word(&mut self) -> bool {
+        self.try_delete_prev_word().expect("valid_cursor")
+    }
+
+    /// Deletes the previous word.
+    pub fn try_delete_prev_word(&mut self) -> Result<bool, TextError> {
         if self.has_selection() {
-            self.delete_range(self.selection())
+            self.try_delete_range(self.selection())
         } else {
             let cursor = self.cursor();
 
-            let end = self.prev_word_end(cursor);
+            let end = self.try_prev_word_end(cursor)?;
             if end != cursor {
-                self.delete_range(end..cursor)
+                self.try_delete_range(end..cursor)
             } else {
-                let start = self.prev_word_start(cursor);
-                self.delete_range(start..cursor)
+                let start = self.try_prev_word_start(cursor)?;
+                self.try_delete_range(start..cursor)
+            }
+        }
+    }
+
+    /// Deletes everything from the start of the line to the cursor.
+    #[inline]
+    pub fn delete_to_line_start(&mut self) -> bool {
+        let cursor = self.cursor();
+        self.delete_range(0..cursor)
+    }
+
+    /// Deletes everything from the cursor to the end of the line.
+    #[inline]
+    pub fn delete_to_line_end(&mut self) -> bool {
+        let cursor = self.cursor();
+        self.delete_range(cursor..self.len())
+    }
+
+    /// Find the matching bracket for the bracket at pos.
+    /// Supports `()`, `[]` and `{}`.
+    ///
+    /// Returns None if there is no bracket at pos or if
+    /// the brackets are unbalanced.
+    pub fn matching_bracket(&self, pos: upos_type) -> Option<upos_type> {
+        let mut it = self.try_text_graphemes(pos).ok()?;
+        let g = it.next()?;
+        let (open, close, forward) = match g.grapheme() {
+            "(" => ("(", ")", true),
+            "[" => ("[", "]", true),
+            "{" => ("{", "}", true),
+            ")" => (")", "(", false),
+            "]" => ("]", "[", false),
+            "}" => ("}", "{", false),
+            _ => return None,
+        };
+
+        let mut depth = 1;
+        let mut p = pos;
+        if forward {
+            for g in it {
+                p += 1;
+                if g == open {
+                    depth += 1;
+                } else if g == close {
+                    depth -= 1;
+                    if depth == 0 {
+                        return Some(p);
+                    }
+                }
             }
+        } else {
+            let mut it = self.try_text_graphemes(pos).ok()?;
+            while let Some(g) = it.prev() {
+                p -= 1;
+                if g == open {
+                    depth += 1;
+                } else if g == close {
+                    depth -= 1;
+                    if depth == 0 {
+                        return Some(p);
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    /// Select from the bracket at the cursor to the matching bracket.
+    /// Both brackets are included.
+    pub fn select_to_matching_bracket(&mut self) -> bool {
+        let cursor = self.cursor();
+        let Some(other) = self.matching_bracket(cursor) else {
+            return false;
+        };
+        if other > cursor {
+            self.set_selection(cursor, other + 1)
+        } else {
+            self.set_selection(cursor + 1, other)
+        }
+    }
+
+    /// Delete from the bracket at the cursor to the matching bracket.
+    /// Both brackets are included.
+    pub fn delete_to_matching_bracket(&mut self) -> bool {
+        let cursor = self.cursor();
+        let Some(other) = self.matching_bracket(cursor) else {
+            return false;
+        };
+        if other > cursor {
+            self.delete_range(cursor..other + 1)
+        } else {
+            self.delete_range(other..cursor + 1)
         }
     }
 
     /// Move to the next char.
     #[inline]
     pub fn move_right(&mut self, extend_selection: bool) -> bool {
-        let c = min(self.cursor() + 1, self.len());
+        self.try_move_right(extend_selection).expect("valid_cursor")
+    }
+
+    /// Move to the next char.
+    pub fn try_move_right(&mut self, extend_selection: bool) -> Result<bool, TextError> {
+        let c = min(self.cursor() + 1, self.value.line_width(0)?);
+        let c = self.set_cursor(c, extend_selection);
+        let s = self.scroll_cursor_to_visible();
+        Ok(c || s)
+    }
+
+    /// Move n chars to the right. Stops at the end of the text.
+    #[inline]
+    pub fn move_right_by(&mut self, n: upos_type, extend_selection: bool) -> bool {
+        let c = min(self.cursor().saturating_add(n), self.len());
+        let c = self.set_cursor(c, extend_selection);
+        let s = self.scroll_cursor_to_visible();
+        c || s
+    }
+
+    /// Move n chars to the left. Stops at the start of the text.
+    #[inline]
+    pub fn move_left_by(&mut self, n: upos_type, extend_selection: bool) -> bool {
+        let c = self.cursor().saturating_sub(n);
         let c = self.set_cursor(c, extend_selection);
         let s = self.scroll_cursor_to_visible();
         c || s
@@ -1051,28 +3339,44 @@ impl TextInputState {
     /// End of line
     #[inline]
     pub fn move_to_line_end(&mut self, extend_selection: bool) -> bool {
-        let c = self.len();
+        self.try_move_to_line_end(extend_selection)
+            .expect("valid_cursor")
+    }
+
+    /// End of line
+    pub fn try_move_to_line_end(&mut self, extend_selection: bool) -> Result<bool, TextError> {
+        let c = self.value.line_width(0)?;
         let c = self.set_cursor(c, extend_selection);
         let s = self.scroll_cursor_to_visible();
-        c || s
+        Ok(c || s)
     }
 
     #[inline]
     pub fn move_to_next_word(&mut self, extend_selection: bool) -> bool {
+        self.try_move_to_next_word(extend_selection)
+            .expect("valid_cursor")
+    }
+
+    pub fn try_move_to_next_word(&mut self, extend_selection: bool) -> Result<bool, TextError> {
         let cursor = self.cursor();
-        let end = self.next_word_end(cursor);
+        let end = self.try_next_word_end(cursor)?;
         let c = self.set_cursor(end, extend_selection);
         let s = self.scroll_cursor_to_visible();
-        c || s
+        Ok(c || s)
     }
 
     #[inline]
     pub fn move_to_prev_word(&mut self, extend_selection: bool) -> bool {
+        self.try_move_to_prev_word(extend_selection)
+            .expect("valid_cursor")
+    }
+
+    pub fn try_move_to_prev_word(&mut self, extend_selection: bool) -> Result<bool, TextError> {
         let cursor = self.cursor();
-        let start = self.prev_word_start(cursor);
+        let start = self.try_prev_word_start(cursor)?;
         let c = self.set_cursor(start, extend_selection);
         let s = self.scroll_cursor_to_visible();
-        c || s
+        Ok(c || s)
     }
 }
 
@@ -1081,17 +3385,7 @@ impl HasScreenCursor for TextInputState {
     #[inline]
     fn screen_cursor(&self) -> Option<(u16, u16)> {
         if self.is_focused() {
-            let cx = self.cursor();
-            let ox = self.offset();
-
-            if cx < ox {
-                None
-            } else if cx > ox + (self.inner.width + self.dark_offset.0) as upos_type {
-                None
-            } else {
-                self.col_to_screen(cx)
-                    .map(|sc| (self.inner.x + sc, self.inner.y))
-            }
+            self.caret_position()
         } else {
             None
         }
@@ -1108,6 +3402,68 @@ impl RelocatableState for TextInputState {
 }
 
 impl TextInputState {
+    /// The current text cursor as an absolute screen position.
+    ///
+    /// Other than [HasScreenCursor::screen_cursor()] this doesn't
+    /// care for the focus. Use it if you want to draw your own caret.
+    /// Returns None if the cursor is scrolled out of view.
+    pub fn caret_position(&self) -> Option<(u16, u16)> {
+        let cx = self.cursor();
+        let ox = self.offset();
+
+        if cx < ox {
+            None
+        } else if cx >= ox + (self.inner.width + self.dark_offset.0) as upos_type {
+            None
+        } else {
+            let pw = self.preedit_width(self.preedit_cursor);
+            self.col_to_screen(cx)
+                .map(|sc| sc + pw)
+                .filter(|sc| *sc < self.inner.width)
+                .map(|sc| (self.inner.x + sc, self.inner.y))
+        }
+    }
+
+    /// Display width of the grapheme at the cursor.
+    ///
+    /// This is 2 for wide graphemes and 1 at the end of the text.
+    /// Together with [TextInput::cursor_style] it can be used to
+    /// paint a block cursor.
+    pub fn caret_width(&self) -> u16 {
+        if let Some(g) = self.glyph_at(self.cursor()) {
+            max(g.screen_width, 1)
+        } else {
+            1
+        }
+    }
+
+    /// Suggested cursor shape for the current editing mode.
+    ///
+    /// TextInput always inserts, so this is [CaretShape::Bar].
+    /// Apps that control the terminal cursor can use this after render.
+    pub fn caret_style_hint(&self) -> CaretShape {
+        CaretShape::Bar
+    }
+
+    /// The widget is focused and the cursor is not scrolled out of view.
+    /// This is the case when [HasScreenCursor::screen_cursor()] returns a value.
+    pub fn is_caret_visible(&self) -> bool {
+        self.is_focused() && self.caret_position().is_some()
+    }
+
+    /// Hit-test for an absolute screen position.
+    ///
+    /// Returns the grapheme position if it is inside the text area.
+    pub fn hit(&self, col: u16, row: u16) -> Hit {
+        if self.inner.contains((col, row).into()) {
+            Hit::Text(self.screen_to_col((col - self.inner.x) as i16))
+        } else if self.area.contains((col, row).into()) {
+            Hit::Border
+        } else {
+            Hit::Outside
+        }
+    }
+
     /// Converts from a widget relative screen coordinate to a grapheme index.
     /// x is the relative screen position.
     pub fn screen_to_col(&self, scx: i16) -> upos_type {
@@ -1160,6 +3516,35 @@ impl TextInputState {
         }
     }
 
+    /// Screen information for the glyph at the given grapheme position.
+    /// The screen position is relative to the inner area.
+    ///
+    /// Returns None if the glyph is not visible.
+    pub fn glyph_at(&self, pos: upos_type) -> Option<GlyphInfo> {
+        self.positions().find(|g| g.pos == pos)
+    }
+
+    /// Screen information for all visible glyphs.
+    ///
+    /// This uses the current offset, the screen positions are
+    /// relative to the inner area.
+    ///
+    /// Gives the grapheme position, byte range, screen position
+    /// and display width in one pass.
+    pub fn positions(&self) -> impl Iterator<Item = GlyphInfo> + '_ {
+        let ox = self.offset();
+        let dark_offset = self.dark_offset.0;
+
+        self.glyphs(ox as u16, self.inner.width + dark_offset)
+            .filter(move |g| g.screen_pos().0 >= dark_offset)
+            .map(move |g| GlyphInfo {
+                pos: g.pos().x,
+                screen_pos: g.screen_pos().0 - dark_offset,
+                screen_width: g.screen_width(),
+                text_bytes: g.text_bytes(),
+            })
+    }
+
     /// Set the cursor position from a screen position relative to the origin
     /// of the widget. This value can be negative, which selects a currently
     /// not visible position and scrolls to it.
@@ -1206,16 +3591,77 @@ impl TextInputState {
         c || s
     }
 
-    /// Scrolling
+    /// Step width for PageUp/PageDown.
+    fn page_len(&self) -> upos_type {
+        max(self.inner.width, 1) as upos_type
+    }
+
+    /// Scroll left by delta graphemes.
+    ///
+    /// For wide graphemes this scrolls more than delta display columns.
+    /// See [TextInputState::scroll_left_display()].
     pub fn scroll_left(&mut self, delta: upos_type) -> bool {
+        let old_offset = self.offset;
         self.set_offset(self.offset.saturating_sub(delta));
-        true
+        self.offset != old_offset
     }
 
-    /// Scrolling
+    /// Scroll right by delta graphemes.
+    ///
+    /// For wide graphemes this scrolls more than delta display columns.
+    /// See [TextInputState::scroll_right_display()].
+    ///
+    /// This stops at the end of the text.
     pub fn scroll_right(&mut self, delta: upos_type) -> bool {
-        self.set_offset(self.offset + delta);
-        true
+        let old_offset = self.offset;
+        self.set_offset(min(self.offset.saturating_add(delta), self.len()));
+        self.offset != old_offset
+    }
+
+    /// Scroll left by delta display columns.
+    ///
+    /// Wide graphemes are not split, the offset moves to the start
+    /// of the grapheme that covers the target column.
+    pub fn scroll_left_display(&mut self, delta: upos_type) -> bool {
+        let old_offset = self.offset;
+        let target = self.display_offset().saturating_sub(delta);
+        let new_offset = self
+            .display_columns()
+            .take_while(|c| *c <= target)
+            .count()
+            .saturating_sub(1);
+        self.set_offset(new_offset as upos_type);
+        self.offset != old_offset
+    }
+
+    /// Scroll right by delta display columns.
+    ///
+    /// Wide graphemes are not split, the offset moves to the start
+    /// of the first grapheme at or after the target column. This
+    /// stops at the end of the text.
+    pub fn scroll_right_display(&mut self, delta: upos_type) -> bool {
+        let old_offset = self.offset;
+        let target = self.display_offset().saturating_add(delta);
+        let new_offset = min(
+            self.display_columns().take_while(|c| *c < target).count() as upos_type,
+            self.len(),
+        );
+        self.set_offset(max(new_offset, self.offset));
+        self.offset != old_offset
+    }
+
+    /// Continue scrolling while a mouse drag is held outside
+    /// of the widget.
+    ///
+    /// The terminal sends no events while the mouse doesn't move,
+    /// call this periodically (e.g. from a timer) to keep extending
+    /// the selection.
+    pub fn drag_autoscroll(&mut self) -> bool {
+        if let Some(c) = self.drag_overflow {
+            self.set_screen_cursor(c, true)
+        } else {
+            false
+        }
     }
 
     /// Change the offset in a way that the cursor is visible.
@@ -1224,11 +3670,20 @@ impl TextInputState {
 
         let c = self.cursor();
         let o = self.offset();
-
-        let no = if c < o {
-            c
-        } else if c >= o + (self.inner.width + self.dark_offset.0) as upos_type {
-            c.saturating_sub((self.inner.width + self.dark_offset.0) as upos_type)
+        let w = (self.inner.width + self.dark_offset.0) as upos_type;
+        let so = min(self.scroll_off as upos_type, w.saturating_sub(1) / 2);
+        // the preedit is displayed at the cursor.
+        let pc = self.preedit_width(self.preedit_cursor) as upos_type;
+        let pw = self.preedit_width(upos_type::MAX) as upos_type;
+
+        // the caret needs a cell of its own, so it must stay left of o + w.
+        let no = if c < o + so {
+            c.saturating_sub(so)
+        } else if c + pc + so >= o + w {
+            min(
+                (c + pc + so + 1).saturating_sub(w),
+                (self.len() + pw + 1).saturating_sub(w),
+            )
         } else {
             o
         };
@@ -1239,89 +3694,213 @@ impl TextInputState {
     }
 }
 
-impl HandleEvent<crossterm::event::Event, Regular, TextOutcome> for TextInputState {
-    fn handle(&mut self, event: &crossterm::event::Event, _keymap: Regular) -> TextOutcome {
+/// Name of the action that handled an event.
+///
+/// The event-handling is generic over this, so the normal event-handling
+/// uses () and doesn't carry the names at all, while
+/// [handle_events_debug] gets them from the same match arms.
+trait ActionName: Copy {
+    fn name(name: &'static str) -> Self;
+}
+
+impl ActionName for () {
+    #[inline(always)]
+    fn name(_name: &'static str) -> Self {}
+}
+
+impl ActionName for &'static str {
+    #[inline(always)]
+    fn name(name: &'static str) -> Self {
+        name
+    }
+}
+
+impl TextInputState {
+    /// Regular event-handling. Returns the outcome and the name of
+    /// the action that handled the event.
+    fn handle_regular<L: ActionName>(
+        &mut self,
+        event: &crossterm::event::Event,
+    ) -> (TextOutcome, L) {
         // small helper ...
         fn tc(r: bool) -> TextOutcome {
-            if r {
-                TextOutcome::TextChanged
-            } else {
-                TextOutcome::Unchanged
-            }
+            TextOutcome::edit(r, false)
         }
 
-        let mut r = if self.is_focused() {
+        let trimmed = if self.trim_on_blur && self.focus.lost() {
+            self.trim()
+        } else {
+            false
+        };
+
+        let mut r = if self.is_focused() && !self.read_only {
             match event {
                 ct_event!(key press c)
                 | ct_event!(key press SHIFT-c)
-                | ct_event!(key press CONTROL_ALT-c) => tc(self.insert_char(*c)),
-                ct_event!(keycode press Tab) => {
-                    // ignore tab from focus
-                    tc(if !self.focus.gained() {
-                        self.insert_tab()
-                    } else {
-                        false
-                    })
+                | ct_event!(key press CONTROL_ALT-c) => {
+                    (tc(self.insert_char(*c)), L::name("insert_char"))
+                }
+                ct_event!(keycode press Tab) if self.tab_action != TabAction::FocusNext => {
+                    match self.tab_action {
+                        TabAction::Insert => (tc(self.insert_tab()), L::name("insert_tab")),
+                        // ignore tab from focus
+                        _ if self.focus.gained() => (TextOutcome::Unchanged, L::name("ignore_tab")),
+                        _ => (tc(self.insert_tab()), L::name("insert_tab")),
+                    }
+                }
+                ct_event!(keycode press Enter) if self.enter_inserts_space => {
+                    (tc(self.insert_char('\n')), L::name("insert_char"))
+                }
+                ct_event!(keycode press Backspace) => {
+                    (self.delete_prev_char(), L::name("delete_prev_char"))
+                }
+                ct_event!(keycode press Delete) => {
+                    (self.delete_next_char(), L::name("delete_next_char"))
                 }
-                ct_event!(keycode press Backspace) => tc(self.delete_prev_char()),
-                ct_event!(keycode press Delete) => tc(self.delete_next_char()),
                 ct_event!(keycode press CONTROL-Backspace)
-                | ct_event!(keycode press ALT-Backspace) => tc(self.delete_prev_word()),
-                ct_event!(keycode press CONTROL-Delete) => tc(self.delete_next_word()),
-                ct_event!(key press CONTROL-'x') => tc(self.cut_to_clip()),
-                ct_event!(key press CONTROL-'v') => tc(self.paste_from_clip()),
-                ct_event!(key press CONTROL-'d') => tc(self.clear()),
-                ct_event!(key press CONTROL-'z') => tc(self.undo()),
-                ct_event!(key press CONTROL_SHIFT-'Z') => tc(self.redo()),
+                | ct_event!(keycode press ALT-Backspace) => {
+                    (tc(self.delete_prev_word()), L::name("delete_prev_word"))
+                }
+                ct_event!(keycode press CONTROL-Delete) => {
+                    (tc(self.delete_next_word()), L::name("delete_next_word"))
+                }
+                ct_event!(key press CONTROL-'u') => (
+                    tc(self.delete_to_line_start()),
+                    L::name("delete_to_line_start"),
+                ),
+                ct_event!(key press CONTROL-'k') => {
+                    (tc(self.delete_to_line_end()), L::name("delete_to_line_end"))
+                }
+                ct_event!(key press CONTROL-'x') => {
+                    (tc(self.cut_to_clip()), L::name("cut_to_clip"))
+                }
+                ct_event!(key press CONTROL-'v') => {
+                    (tc(self.paste_from_clip()), L::name("paste_from_clip"))
+                }
+                ct_event!(key press CONTROL-'z') => (tc(self.undo()), L::name("undo")),
+                ct_event!(key press CONTROL_SHIFT-'Z') => (tc(self.redo()), L::name("redo")),
 
                 ct_event!(key release _)
                 | ct_event!(key release SHIFT-_)
                 | ct_event!(key release CONTROL_ALT-_)
-                | ct_event!(keycode release Tab)
                 | ct_event!(keycode release Backspace)
                 | ct_event!(keycode release Delete)
                 | ct_event!(keycode release CONTROL-Backspace)
                 | ct_event!(keycode release ALT-Backspace)
                 | ct_event!(keycode release CONTROL-Delete)
+                | ct_event!(key release CONTROL-'u')
+                | ct_event!(key release CONTROL-'k')
                 | ct_event!(key release CONTROL-'x')
                 | ct_event!(key release CONTROL-'v')
-                | ct_event!(key release CONTROL-'d')
                 | ct_event!(key release CONTROL-'y')
                 | ct_event!(key release CONTROL-'z')
-                | ct_event!(key release CONTROL_SHIFT-'Z') => TextOutcome::Unchanged,
+                | ct_event!(key release CONTROL_SHIFT-'Z') => {
+                    (TextOutcome::Unchanged, L::name("key_release"))
+                }
+                ct_event!(keycode release Tab) if self.tab_action != TabAction::FocusNext => {
+                    (TextOutcome::Unchanged, L::name("key_release"))
+                }
+                ct_event!(keycode release Enter) if self.enter_inserts_space => {
+                    (TextOutcome::Unchanged, L::name("key_release"))
+                }
 
-                _ => TextOutcome::Continue,
+                _ => (TextOutcome::Continue, L::name("")),
             }
         } else {
-            TextOutcome::Continue
+            (TextOutcome::Continue, L::name(""))
         };
-        if r == TextOutcome::Continue {
-            r = self.handle(event, ReadOnly);
+        if r.0 == TextOutcome::Continue {
+            r = self.handle_readonly(event);
+        }
+        if trimmed && r.0 < TextOutcome::TextChanged {
+            // the change comes from the trim, not from the event.
+            r = (TextOutcome::TextChanged, L::name("trim"));
         }
         r
     }
-}
 
-impl HandleEvent<crossterm::event::Event, ReadOnly, TextOutcome> for TextInputState {
-    fn handle(&mut self, event: &crossterm::event::Event, _keymap: ReadOnly) -> TextOutcome {
+    /// Navigation event-handling. Returns the outcome and the name of
+    /// the action that handled the event.
+    fn handle_readonly<L: ActionName>(
+        &mut self,
+        event: &crossterm::event::Event,
+    ) -> (TextOutcome, L) {
         let mut r = if self.is_focused() {
             match event {
-                ct_event!(keycode press Left) => self.move_left(false).into(),
-                ct_event!(keycode press Right) => self.move_right(false).into(),
-                ct_event!(keycode press CONTROL-Left) => self.move_to_prev_word(false).into(),
-                ct_event!(keycode press CONTROL-Right) => self.move_to_next_word(false).into(),
-                ct_event!(keycode press Home) => self.move_to_line_start(false).into(),
-                ct_event!(keycode press End) => self.move_to_line_end(false).into(),
-                ct_event!(keycode press SHIFT-Left) => self.move_left(true).into(),
-                ct_event!(keycode press SHIFT-Right) => self.move_right(true).into(),
-                ct_event!(keycode press CONTROL_SHIFT-Left) => self.move_to_prev_word(true).into(),
-                ct_event!(keycode press CONTROL_SHIFT-Right) => self.move_to_next_word(true).into(),
-                ct_event!(keycode press SHIFT-Home) => self.move_to_line_start(true).into(),
-                ct_event!(keycode press SHIFT-End) => self.move_to_line_end(true).into(),
-                ct_event!(keycode press ALT-Left) => self.scroll_left(1).into(),
-                ct_event!(keycode press ALT-Right) => self.scroll_right(1).into(),
-                ct_event!(key press CONTROL-'a') => self.select_all().into(),
-                ct_event!(key press CONTROL-'c') => self.copy_to_clip().into(),
+                ct_event!(keycode press Left) => {
+                    (self.move_left(false).into(), L::name("move_left"))
+                }
+                ct_event!(keycode press Right) => {
+                    (self.move_right(false).into(), L::name("move_right"))
+                }
+                ct_event!(keycode press CONTROL-Left) => (
+                    self.move_to_prev_word(false).into(),
+                    L::name("move_to_prev_word"),
+                ),
+                ct_event!(keycode press CONTROL-Right) => (
+                    self.move_to_next_word(false).into(),
+                    L::name("move_to_next_word"),
+                ),
+                ct_event!(keycode press Home) => (
+                    self.move_to_line_start(false).into(),
+                    L::name("move_to_line_start"),
+                ),
+                ct_event!(keycode press End) => (
+                    self.move_to_line_end(false).into(),
+                    L::name("move_to_line_end"),
+                ),
+                ct_event!(keycode press SHIFT-Left) => {
+                    (self.move_left(true).into(), L::name("move_left"))
+                }
+                ct_event!(keycode press SHIFT-Right) => {
+                    (self.move_right(true).into(), L::name("move_right"))
+                }
+                ct_event!(keycode press CONTROL_SHIFT-Left) => (
+                    self.move_to_prev_word(true).into(),
+                    L::name("move_to_prev_word"),
+                ),
+                ct_event!(keycode press CONTROL_SHIFT-Right) => (
+                    self.move_to_next_word(true).into(),
+                    L::name("move_to_next_word"),
+                ),
+                ct_event!(keycode press SHIFT-Home) => (
+                    self.move_to_line_start(true).into(),
+                    L::name("move_to_line_start"),
+                ),
+                ct_event!(keycode press SHIFT-End) => (
+                    self.move_to_line_end(true).into(),
+                    L::name("move_to_line_end"),
+                ),
+                ct_event!(keycode press PageUp) => (
+                    self.move_left_by(self.page_len(), false).into(),
+                    L::name("move_left_by"),
+                ),
+                ct_event!(keycode press PageDown) => (
+                    self.move_right_by(self.page_len(), false).into(),
+                    L::name("move_right_by"),
+                ),
+                ct_event!(keycode press SHIFT-PageUp) => (
+                    self.move_left_by(self.page_len(), true).into(),
+                    L::name("move_left_by"),
+                ),
+                ct_event!(keycode press SHIFT-PageDown) => (
+                    self.move_right_by(self.page_len(), true).into(),
+                    L::name("move_right_by"),
+                ),
+                ct_event!(keycode press ALT-Left) => (
+                    self.scroll_left(self.scroll_step).into(),
+                    L::name("scroll_left"),
+                ),
+                ct_event!(keycode press ALT-Right) => (
+                    self.scroll_right(self.scroll_step).into(),
+                    L::name("scroll_right"),
+                ),
+                ct_event!(key press CONTROL-'a') => {
+                    (self.select_all().into(), L::name("select_all"))
+                }
+                ct_event!(key press CONTROL-'c') => {
+                    (self.copy_to_clip().into(), L::name("copy_to_clip"))
+                }
 
                 ct_event!(keycode release Left)
                 | ct_event!(keycode release Right)
@@ -1335,70 +3914,190 @@ impl HandleEvent<crossterm::event::Event, ReadOnly, TextOutcome> for TextInputSt
                 | ct_event!(keycode release CONTROL_SHIFT-Right)
                 | ct_event!(keycode release SHIFT-Home)
                 | ct_event!(keycode release SHIFT-End)
+                | ct_event!(keycode release PageUp)
+                | ct_event!(keycode release PageDown)
+                | ct_event!(keycode release SHIFT-PageUp)
+                | ct_event!(keycode release SHIFT-PageDown)
                 | ct_event!(key release CONTROL-'a')
-                | ct_event!(key release CONTROL-'c') => TextOutcome::Unchanged,
+                | ct_event!(key release CONTROL-'c') => {
+                    (TextOutcome::Unchanged, L::name("key_release"))
+                }
 
-                _ => TextOutcome::Continue,
+                _ => (TextOutcome::Continue, L::name("")),
             }
         } else {
-            TextOutcome::Continue
+            (TextOutcome::Continue, L::name(""))
         };
 
-        if r == TextOutcome::Continue {
-            r = self.handle(event, MouseOnly);
+        if r.0 == TextOutcome::Continue {
+            r = self.handle_mouse(event);
         }
         r
     }
-}
 
-impl HandleEvent<crossterm::event::Event, MouseOnly, TextOutcome> for TextInputState {
-    fn handle(&mut self, event: &crossterm::event::Event, _keymap: MouseOnly) -> TextOutcome {
+    /// Mouse event-handling. Returns the outcome and the name of
+    /// the action that handled the event.
+    fn handle_mouse<L: ActionName>(&mut self, event: &crossterm::event::Event) -> (TextOutcome, L) {
+        if let ct_event!(mouse any for m) = event {
+            if m.kind != MouseEventKind::Drag(MouseButton::Left) {
+                self.drag_overflow = None;
+            }
+        }
+
         match event {
             ct_event!(mouse any for m) if self.mouse.drag(self.inner, m) => {
                 let c = (m.column as i16) - (self.inner.x as i16);
-                self.set_screen_cursor(c, true).into()
+                self.drag_overflow = if m.column < self.inner.x || m.column >= self.inner.right() {
+                    Some(c)
+                } else {
+                    None
+                };
+                if self.selection_granularity == SelectionGranularity::Word {
+                    (
+                        self.set_screen_cursor_words(c, true).into(),
+                        L::name("set_screen_cursor_words"),
+                    )
+                } else {
+                    (
+                        self.set_screen_cursor(c, true).into(),
+                        L::name("set_screen_cursor"),
+                    )
+                }
             }
             ct_event!(mouse any for m) if self.mouse.drag2(self.inner, m, KeyModifiers::ALT) => {
                 let cx = m.column as i16 - self.inner.x as i16;
-                self.set_screen_cursor_words(cx, true).into()
+                (
+                    self.set_screen_cursor_words(cx, true).into(),
+                    L::name("set_screen_cursor_words"),
+                )
             }
             ct_event!(mouse any for m) if self.mouse.doubleclick(self.inner, m) => {
                 let tx = self.screen_to_col(m.column as i16 - self.inner.x as i16);
                 let start = self.word_start(tx);
                 let end = self.word_end(tx);
-                self.set_selection(start, end).into()
+                self.selection_granularity = SelectionGranularity::Word;
+                (
+                    self.set_selection(start, end).into(),
+                    L::name("select_word"),
+                )
             }
             ct_event!(mouse down Left for column,row) => {
-                if self.gained_focus() {
+                if self.gained_focus() && !self.focus_click_moves_caret {
                     // don't react to the first click that's for
                     // focus. this one shouldn't demolish the selection.
-                    TextOutcome::Unchanged
-                } else if self.inner.contains((*column, *row).into()) {
-                    let c = (column - self.inner.x) as i16;
-                    self.set_screen_cursor(c, false).into()
+                    (TextOutcome::Unchanged, L::name("focus_click"))
+                } else if let Hit::Text(c) = self.hit(*column, *row) {
+                    self.selection_granularity = SelectionGranularity::Char;
+                    let c = self.set_cursor(c, false);
+                    let s = self.scroll_cursor_to_visible();
+                    ((c || s).into(), L::name("set_cursor"))
                 } else {
-                    TextOutcome::Continue
+                    (TextOutcome::Continue, L::name(""))
                 }
             }
-            ct_event!(mouse down CONTROL-Left for column,row) => {
-                if self.inner.contains((*column, *row).into()) {
-                    let cx = (column - self.inner.x) as i16;
-                    self.set_screen_cursor(cx, true).into()
+            ct_event!(mouse down SHIFT-Left for column,row)
+            | ct_event!(mouse down CONTROL-Left for column,row) => {
+                if let Hit::Text(c) = self.hit(*column, *row) {
+                    let c = self.set_cursor(c, true);
+                    let s = self.scroll_cursor_to_visible();
+                    ((c || s).into(), L::name("set_cursor"))
                 } else {
-                    TextOutcome::Continue
+                    (TextOutcome::Continue, L::name(""))
                 }
             }
             ct_event!(mouse down ALT-Left for column,row) => {
                 if self.inner.contains((*column, *row).into()) {
                     let cx = (column - self.inner.x) as i16;
-                    self.set_screen_cursor_words(cx, true).into()
+                    (
+                        self.set_screen_cursor_words(cx, true).into(),
+                        L::name("set_screen_cursor_words"),
+                    )
                 } else {
-                    TextOutcome::Continue
+                    (TextOutcome::Continue, L::name(""))
                 }
             }
-            _ => TextOutcome::Continue,
+            ct_event!(mouse any for m)
+                if m.kind == MouseEventKind::ScrollLeft
+                    && self.inner.contains((m.column, m.row).into()) =>
+            {
+                (
+                    self.scroll_left(self.scroll_step).into(),
+                    L::name("scroll_left"),
+                )
+            }
+            ct_event!(mouse any for m)
+                if m.kind == MouseEventKind::ScrollRight
+                    && self.inner.contains((m.column, m.row).into()) =>
+            {
+                (
+                    self.scroll_right(self.scroll_step).into(),
+                    L::name("scroll_right"),
+                )
+            }
+            _ => (TextOutcome::Continue, L::name("")),
+        }
+    }
+}
+
+impl HandleEvent<crossterm::event::Event, Regular, TextOutcome> for TextInputState {
+    fn handle(&mut self, event: &crossterm::event::Event, _keymap: Regular) -> TextOutcome {
+        self.handle_regular::<()>(event).0
+    }
+}
+
+impl HandleEvent<crossterm::event::Event, ReadOnly, TextOutcome> for TextInputState {
+    fn handle(&mut self, event: &crossterm::event::Event, _keymap: ReadOnly) -> TextOutcome {
+        self.handle_readonly::<()>(event).0
+    }
+}
+
+impl HandleEvent<crossterm::event::Event, MouseOnly, TextOutcome> for TextInputState {
+    fn handle(&mut self, event: &crossterm::event::Event, _keymap: MouseOnly) -> TextOutcome {
+        self.handle_mouse::<()>(event).0
+    }
+}
+
+/// Replace line-breaks with a space and remove other control characters.
+fn sanitize_text(s: String) -> String {
+    if s.contains(is_invalid_char) {
+        sanitize_str(&s, " ").into_owned()
+    } else {
+        s
+    }
+}
+
+/// Replace line-breaks with the given str and remove other
+/// control characters. Used for everything that ends up in the text.
+fn sanitize_str<'b>(s: &'b str, newline: &str) -> Cow<'b, str> {
+    if !s.contains(is_invalid_char) {
+        return Cow::Borrowed(s);
+    }
+    let mut v = String::with_capacity(s.len());
+    let mut it = s.chars().peekable();
+    while let Some(c) = it.next() {
+        match c {
+            '\r' if it.peek() == Some(&'\n') => {
+                it.next();
+                v.push_str(newline);
+            }
+            '\n' | '\r' => v.push_str(newline),
+            c if is_invalid_char(c) => {}
+            c => v.push(c),
         }
     }
+    Cow::Owned(v)
+}
+
+/// Control characters are not allowed in a single line input.
+/// Tabs are ok.
+fn is_invalid_char(c: char) -> bool {
+    c != '\t' && c.is_control()
+}
+
+/// Combining marks and other chars that don't start a
+/// grapheme of their own.
+fn is_zero_width(c: char) -> bool {
+    !c.is_control() && c.width() == Some(0)
 }
 
 /// Handle all events.
@@ -1413,6 +4112,29 @@ pub fn handle_events(
     state.handle(event, Regular)
 }
 
+/// Handle all events like [handle_events] and name the action
+/// that consumed the event.
+///
+/// The name is the name of the function that has been called,
+/// e.g. "insert_char" or "move_left". Releasing a key gives
+/// "key_release", a change due to trim-on-blur gives "trim".
+/// The name is None if the event was not consumed.
+///
+/// This is meant for debugging key bindings.
+pub fn handle_events_debug(
+    state: &mut TextInputState,
+    focus: bool,
+    event: &crossterm::event::Event,
+) -> (TextOutcome, Option<&'static str>) {
+    state.focus.set(focus);
+    let (r, name) = state.handle_regular::<&'static str>(event);
+    if r == TextOutcome::Continue {
+        (r, None)
+    } else {
+        (r, Some(name))
+    }
+}
+
 /// Handle only navigation events.
 /// Text events are only processed if focus is true.
 /// Mouse events are processed if they are in range.
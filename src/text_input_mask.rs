@@ -75,7 +75,10 @@ use crate::event::{ReadOnly, TextOutcome};
 use crate::text_input::TextInputState;
 use crate::text_mask_core::MaskedCore;
 use crate::undo_buffer::{UndoBuffer, UndoEntry};
-use crate::{ipos_type, upos_type, Cursor, Glyph, Grapheme, HasScreenCursor, TextError, TextStyle};
+use crate::{
+    ipos_type, upos_type, CaretShape, Cursor, Glyph, Grapheme, HasScreenCursor, TextError,
+    TextStyle,
+};
 use crossterm::event::KeyModifiers;
 use format_num_pattern::NumberSymbols;
 use rat_event::util::MouseFlags;
@@ -772,7 +775,7 @@ impl MaskedInputState {
 
     /// Selection.
     #[inline]
-    pub fn selected_text(&self) -> &str {
+    pub fn selected_text(&self) -> Cow<'_, str> {
         self.value.selected_text()
     }
 }
@@ -790,6 +793,13 @@ impl MaskedInputState {
         self.value.text()
     }
 
+    /// Only the entered characters, without the separators
+    /// of the mask and without unfilled slots.
+    #[inline]
+    pub fn value_unmasked(&self) -> String {
+        self.value.value_unmasked()
+    }
+
     /// Text slice as `Cow<str>`. Uses a byte range.
     #[inline]
     pub fn str_slice_byte(&self, range: Range<usize>) -> Cow<'_, str> {
@@ -1182,6 +1192,14 @@ impl MaskedInputState {
             false
         }
     }
+
+    /// Suggested cursor shape for the current editing mode.
+    ///
+    /// Typing overwrites the mask placeholders, so this is
+    /// [CaretShape::Block].
+    pub fn caret_style_hint(&self) -> CaretShape {
+        CaretShape::Block
+    }
 }
 
 impl HasScreenCursor for MaskedInputState {
@@ -600,17 +600,10 @@ impl MaskedCore {
 
     /// Selection.
     #[inline]
-    pub fn selected_text(&self) -> &str {
-        match self
-            .masked
+    pub fn selected_text(&self) -> Cow<'_, str> {
+        self.masked
             .str_slice(self.masked.selection())
             .expect("valid_range")
-        {
-            Cow::Borrowed(v) => v,
-            Cow::Owned(_) => {
-                unreachable!()
-            }
-        }
     }
 }
 
@@ -700,6 +693,8 @@ impl MaskedCore {
         it.set_screen_width(screen_width);
         it.set_tabs(self.masked.tab_width());
         it.set_show_ctrl(self.masked.glyph_ctrl());
+        it.set_tab_glyph(self.masked.tab_glyph());
+        it.set_space_glyph(self.masked.space_glyph());
         it.set_line_break(self.masked.glyph_line_break());
         Ok(it)
     }
@@ -768,6 +763,8 @@ impl MaskedCore {
         it.set_screen_width(screen_width);
         it.set_tabs(self.masked.tab_width());
         it.set_show_ctrl(self.masked.glyph_ctrl());
+        it.set_tab_glyph(self.masked.tab_glyph());
+        it.set_space_glyph(self.masked.space_glyph());
         it.set_line_break(self.masked.glyph_line_break());
         Ok(it)
     }
@@ -829,6 +826,24 @@ impl MaskedCore {
         self.masked.text().as_str()
     }
 
+    /// Only the entered characters.
+    ///
+    /// Drops the separators of the mask and the slots that
+    /// have not been filled and display as a space.
+    pub fn value_unmasked(&self) -> String {
+        let mut buf = String::new();
+        for (g, mask) in self.text().graphemes(true).zip(self.mask.iter()) {
+            if mask.right.is_separator() {
+                continue;
+            }
+            if g == " " && mask.edit.as_ref() == " " {
+                continue;
+            }
+            buf.push_str(g);
+        }
+        buf
+    }
+
     /// Sets the value.
     /// No checks if the value conforms to the mask.
     /// If the value is too short it will be filled with space.
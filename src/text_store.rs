@@ -83,6 +83,26 @@ pub trait TextStore {
     /// Number of lines.
     fn len_lines(&self) -> upos_type;
 
+    /// Length in bytes.
+    ///
+    /// The default implementation goes through all lines, which is O(n).
+    /// Stores that know their length should override this.
+    fn len_bytes(&self) -> usize {
+        self.lines_at(0)
+            .map(|lines| lines.map(|v| v.len()).sum())
+            .unwrap_or_default()
+    }
+
+    /// Length in chars.
+    ///
+    /// The default implementation goes through all lines, which is O(n).
+    /// Stores that know their length should override this.
+    fn len_chars(&self) -> usize {
+        self.lines_at(0)
+            .map(|lines| lines.map(|v| v.chars().count()).sum())
+            .unwrap_or_default()
+    }
+
     /// Insert a char at the given position.
     ///
     /// * range must be a valid range. row <= len_lines, col <= line_width of the row.
@@ -538,6 +558,16 @@ pub(crate) mod text_rope {
             self.text.len_lines() as upos_type
         }
 
+        /// Length in bytes. O(1).
+        fn len_bytes(&self) -> usize {
+            self.text.len_bytes()
+        }
+
+        /// Length in chars. O(1).
+        fn len_chars(&self) -> usize {
+            self.text.len_chars()
+        }
+
         /// Insert a char at the given position.
         ///
         /// * range must be a valid range. row <= len_lines, col <= line_width of the row.
@@ -775,6 +805,8 @@ pub(crate) mod text_string {
         text: String,
         // len as grapheme count
         len: upos_type,
+        // len as char count
+        len_chars: usize,
         // tmp buffer
         buf: String,
     }
@@ -791,6 +823,7 @@ pub(crate) mod text_string {
             Self {
                 text: Default::default(),
                 len: 0,
+                len_chars: 0,
                 buf: Default::default(),
             }
         }
@@ -800,6 +833,7 @@ pub(crate) mod text_string {
             Self {
                 text: t.into(),
                 len: str_len(t),
+                len_chars: t.chars().count(),
                 buf: Default::default(),
             }
         }
@@ -807,9 +841,11 @@ pub(crate) mod text_string {
         /// New from string.
         pub fn new_string(t: String) -> Self {
             let len = str_len(&t);
+            let len_chars = t.chars().count();
             Self {
                 text: t,
                 len,
+                len_chars,
                 buf: Default::default(),
             }
         }
@@ -837,6 +873,7 @@ pub(crate) mod text_string {
         fn set_string(&mut self, t: &str) {
             self.text = t.to_string();
             self.len = str_len(&self.text);
+            self.len_chars = self.text.chars().count();
         }
 
         /// Grapheme position to byte position.
@@ -1097,6 +1134,16 @@ pub(crate) mod text_string {
             1
         }
 
+        /// Length in bytes. O(1).
+        fn len_bytes(&self) -> usize {
+            self.text.len()
+        }
+
+        /// Length in chars. O(1).
+        fn len_chars(&self) -> usize {
+            self.len_chars
+        }
+
         /// Insert a char at the given position.
         ///
         /// * range must be a valid range. row <= len_lines, col <= line_width of the row.
@@ -1123,6 +1170,7 @@ pub(crate) mod text_string {
 
             mem::swap(&mut self.text, &mut self.buf);
             self.len = new_len;
+            self.len_chars += 1;
 
             Ok((
                 TextRange::new((pos.x, 0), (pos.x + (new_len - old_len), 0)),
@@ -1154,6 +1202,7 @@ pub(crate) mod text_string {
 
             mem::swap(&mut self.text, &mut self.buf);
             self.len = new_len;
+            self.len_chars += t.chars().count();
 
             Ok((
                 TextRange::new((pos.x, 0), (pos.x + (new_len - old_len), 0)),
@@ -1188,10 +1237,12 @@ pub(crate) mod text_string {
             let remove_str = remove.to_string();
             let before_bytes = before.len();
             let remove_bytes = remove.len();
+            let remove_chars = remove.chars().count();
             let new_len = str_len(&self.buf);
 
             mem::swap(&mut self.text, &mut self.buf);
             self.len = new_len;
+            self.len_chars -= remove_chars;
 
             Ok((
                 remove_str,
@@ -1213,6 +1264,7 @@ pub(crate) mod text_string {
 
             mem::swap(&mut self.text, &mut self.buf);
             self.len = new_len;
+            self.len_chars += t.chars().count();
 
             Ok(())
         }
@@ -1222,10 +1274,11 @@ pub(crate) mod text_string {
             let Some((before, after)) = self.text.split_at_checked(byte_range.start) else {
                 return Err(TextError::ByteIndexNotCharBoundary(byte_range.start));
             };
-            let Some((_remove, after)) = after.split_at_checked(byte_range.end - byte_range.start)
+            let Some((remove, after)) = after.split_at_checked(byte_range.end - byte_range.start)
             else {
                 return Err(TextError::ByteIndexNotCharBoundary(byte_range.end));
             };
+            let remove_chars = remove.chars().count();
 
             self.buf.clear();
             self.buf.push_str(before);
@@ -1234,6 +1287,7 @@ pub(crate) mod text_string {
 
             mem::swap(&mut self.text, &mut self.buf);
             self.len = new_len;
+            self.len_chars -= remove_chars;
 
             Ok(())
         }
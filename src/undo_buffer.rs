@@ -49,6 +49,30 @@ pub trait UndoBuffer: DynClone + Debug {
     /// Clear the undo and the replay buffer.
     fn clear(&mut self);
 
+    /// Clear the undo buffer but keep the replay buffer.
+    ///
+    /// The default implementation clears both.
+    fn clear_undo(&mut self) {
+        self.clear();
+    }
+
+    /// Number of operations that can be undone.
+    ///
+    /// This counts single operations, a sequence of changes
+    /// that is undone at once counts as several.
+    ///
+    /// The default implementation returns 0.
+    fn undo_len(&self) -> usize {
+        0
+    }
+
+    /// Number of operations that can be redone.
+    ///
+    /// The default implementation returns 0.
+    fn redo_len(&self) -> usize {
+        0
+    }
+
     /// Get the list of the next undo operations.
     fn undo(&mut self) -> Vec<&UndoOp>;
 
@@ -78,12 +102,34 @@ pub trait UndoBuffer: DynClone + Debug {
     /// This empties the replay buffer.
     fn recent_replay_log(&mut self) -> Vec<UndoEntry>;
 
+    /// Enable undo for setting/removing styles.
+    ///
+    /// The default implementation does nothing.
+    fn enable_undo_styles(&mut self, undo_styles: bool) {
+        _ = undo_styles;
+    }
+
     /// Is there undo for setting/removing styles.
     fn undo_styles_enabled(&self) -> bool;
 }
 
+/// Encode a replay-log for sending it elsewhere.
+///
+/// The format is JSON.
+#[cfg(feature = "serde")]
+pub fn encode_replay(replay: &[UndoEntry]) -> Vec<u8> {
+    serde_json::to_vec(replay).expect("serializable")
+}
+
+/// Decode a replay-log created with [encode_replay].
+#[cfg(feature = "serde")]
+pub fn decode_replay(data: &[u8]) -> Result<Vec<UndoEntry>, serde_json::Error> {
+    serde_json::from_slice(data)
+}
+
 /// Stores one style change.
 #[derive(Debug, Default, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct StyleChange {
     pub before: Range<usize>,
     pub after: Range<usize>,
@@ -92,6 +138,7 @@ pub struct StyleChange {
 
 /// Stores a text position change.
 #[derive(Debug, Default, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TextPositionChange {
     pub before: TextPosition,
     pub after: TextPosition,
@@ -100,6 +147,7 @@ pub struct TextPositionChange {
 /// Storage for undo.
 #[non_exhaustive]
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct UndoEntry {
     pub sequence: u32,
     pub operation: UndoOp,
@@ -108,6 +156,7 @@ pub struct UndoEntry {
 /// Storage for undo.
 #[non_exhaustive]
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum UndoOp {
     /// Insert a single char/grapheme.
     ///
@@ -727,6 +776,19 @@ impl UndoBuffer for UndoVec {
         self.replay.clear();
     }
 
+    fn clear_undo(&mut self) {
+        self.buf.clear();
+        self.idx = 0;
+    }
+
+    fn undo_len(&self) -> usize {
+        self.idx
+    }
+
+    fn redo_len(&self) -> usize {
+        self.buf.len() - self.idx
+    }
+
     /// Get next undo
     fn undo(&mut self) -> Vec<&UndoOp> {
         if self.idx > 0 {
@@ -792,6 +854,10 @@ impl UndoBuffer for UndoVec {
         mem::take(&mut self.replay)
     }
 
+    fn enable_undo_styles(&mut self, undo_styles: bool) {
+        self.undo_styles = undo_styles;
+    }
+
     fn undo_styles_enabled(&self) -> bool {
         self.undo_styles
     }
@@ -0,0 +1,41 @@
+use rat_text::text_area::{TextArea, TextAreaState};
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::widgets::StatefulWidget;
+
+fn render(state: &mut TextAreaState, width: u16, height: u16) -> Buffer {
+    let area = Rect::new(0, 0, width, height);
+    let mut buf = Buffer::empty(area);
+    TextArea::new().render(area, &mut buf, state);
+    buf
+}
+
+#[test]
+fn test_tab_glyph() {
+    let mut s = TextAreaState::new();
+    s.set_text("a\tb c");
+    s.set_tab_width(4);
+    s.set_show_ctrl(true);
+    s.set_tab_glyph('→');
+    s.set_space_glyph(Some('·'));
+
+    let buf = render(&mut s, 10, 2);
+    assert_eq!(buf[(0, 0)].symbol(), "a");
+    assert_eq!(buf[(1, 0)].symbol(), "→");
+    assert_eq!(buf[(4, 0)].symbol(), "b");
+    assert_eq!(buf[(5, 0)].symbol(), "·");
+    assert_eq!(buf[(6, 0)].symbol(), "c");
+}
+
+#[test]
+fn test_tab_glyph_no_ctrl() {
+    let mut s = TextAreaState::new();
+    s.set_text("a\tb c");
+    s.set_tab_width(4);
+    s.set_tab_glyph('→');
+    s.set_space_glyph(Some('·'));
+
+    let buf = render(&mut s, 10, 2);
+    assert_eq!(buf[(1, 0)].symbol(), " ");
+    assert_eq!(buf[(5, 0)].symbol(), " ");
+}
@@ -140,3 +140,35 @@ fn test_undo2() {
     s.redo();
     assert_eq!(s.text().string(), "asdf\nxjklö\nuiop\n");
 }
+
+#[test]
+fn test_remove_range_cursor() {
+    let mut s = TextCore::<TextRope>::new(None, None);
+    s.set_text(TextRope::new_text("0123456789"));
+
+    s.set_selection(TextPosition::new(2, 0), TextPosition::new(8, 0));
+    s.remove_str_range(TextRange::new((4, 0), (6, 0))).unwrap();
+    assert_eq!(s.text().string(), "01236789");
+    assert_eq!(s.anchor(), TextPosition::new(2, 0));
+    assert_eq!(s.cursor(), TextPosition::new(6, 0));
+
+    // cursor at the start of the selection
+    s.set_selection(TextPosition::new(6, 0), TextPosition::new(1, 0));
+    s.remove_str_range(TextRange::new((3, 0), (5, 0))).unwrap();
+    assert_eq!(s.text().string(), "012789");
+    assert_eq!(s.anchor(), TextPosition::new(4, 0));
+    assert_eq!(s.cursor(), TextPosition::new(1, 0));
+}
+
+#[test]
+fn test_stats() {
+    let mut s = TextCore::<TextRope>::new(None, None);
+    s.set_text(TextRope::new_text("asdf jklö\n  qwer\n"));
+
+    let stats = s.stats();
+    assert_eq!(stats.graphemes, 15);
+    assert_eq!(stats.chars, 17);
+    assert_eq!(stats.bytes, 18);
+    assert_eq!(stats.words, 3);
+    assert_eq!(stats.lines, 3);
+}
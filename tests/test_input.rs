@@ -0,0 +1,2279 @@
+use crossterm::event::{
+    Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers, MouseButton, MouseEvent, MouseEventKind,
+};
+use rat_text::clipboard::{Clipboard, NullClipboard, RingClipboard};
+use rat_text::core::TextString;
+use rat_text::event::TextOutcome;
+use rat_text::text_input::{
+    handle_events, handle_events_debug, handle_readonly_events, GlyphInfo, Hit, InsertResult,
+    PlaceholderWhen, SelectionGranularity, TabAction, TextInput, TextInputState,
+};
+use rat_text::undo_buffer::UndoOp;
+use rat_text::{HasScreenCursor, TextError};
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::widgets::{Block, StatefulWidget};
+use std::cell::Cell;
+use std::cmp::Ordering;
+use std::rc::Rc;
+
+fn render(state: &mut TextInputState, width: u16) -> Buffer {
+    let area = Rect::new(0, 0, width, 1);
+    let mut buf = Buffer::empty(area);
+    TextInput::new().render(area, &mut buf, state);
+    buf
+}
+
+#[test]
+fn test_glyph_at() {
+    let mut s = TextInputState::new();
+    s.set_text("a日b");
+    render(&mut s, 10);
+
+    assert_eq!(
+        s.glyph_at(0),
+        Some(GlyphInfo {
+            pos: 0,
+            screen_pos: 0,
+            screen_width: 1,
+            text_bytes: 0..1,
+        })
+    );
+    assert_eq!(
+        s.glyph_at(1),
+        Some(GlyphInfo {
+            pos: 1,
+            screen_pos: 1,
+            screen_width: 2,
+            text_bytes: 1..4,
+        })
+    );
+    assert_eq!(
+        s.glyph_at(2),
+        Some(GlyphInfo {
+            pos: 2,
+            screen_pos: 3,
+            screen_width: 1,
+            text_bytes: 4..5,
+        })
+    );
+    assert_eq!(s.glyph_at(3), None);
+}
+
+#[test]
+fn test_selection_bytes() {
+    let mut s = TextInputState::new();
+    s.set_text("aöb日c");
+
+    assert!(s.set_selection_bytes(1..3).unwrap());
+    assert_eq!(s.selection(), 1..2);
+    assert_eq!(s.selection_bytes(), 1..3);
+
+    // inside of a grapheme
+    s.set_selection_bytes(2..5).unwrap();
+    assert_eq!(s.selection(), 1..4);
+    assert_eq!(s.selection_bytes(), 1..7);
+
+    s.set_selection_bytes(8..8).unwrap();
+    assert_eq!(s.cursor(), 5);
+    assert!(!s.has_selection());
+
+    assert!(s.set_selection_bytes(0..9).is_err());
+}
+
+#[test]
+fn test_trim_on_blur() {
+    let mut s = TextInputState::new();
+    s.set_text("  abc ");
+    s.set_cursor(6, false);
+    s.set_trim_on_blur(true);
+
+    s.focus.set_lost(true);
+    let r = handle_events(&mut s, false, &Event::FocusLost);
+    assert_eq!(r, TextOutcome::TextChanged);
+    assert_eq!(s.text(), "abc");
+    assert_eq!(s.cursor(), 3);
+
+    s.undo();
+    assert_eq!(s.text(), "  abc ");
+
+    s.set_text("   ");
+    assert!(s.trim());
+    assert_eq!(s.text(), "");
+    assert!(!s.trim());
+}
+
+#[test]
+fn test_set_text() {
+    let mut s = TextInputState::new();
+    s.set_text("abc\r\ndef\nghi\x07\tjkl");
+    assert_eq!(s.text(), "abc def ghi\tjkl");
+
+    assert!(s.try_set_text("abc\ndef").is_err());
+    assert_eq!(s.text(), "abc def ghi\tjkl");
+    assert!(s.try_set_text("abc\tdef").is_ok());
+    assert_eq!(s.text(), "abc\tdef");
+
+    // insert and set_text agree about valid text.
+    let mut s2 = TextInputState::new();
+    let r = s2.insert_str_result("abc\r\ndef\nghi\x07\tjkl");
+    assert_eq!(s2.text(), "abc def ghi\tjkl");
+    assert_eq!(r.rejected, 1);
+}
+
+fn click(column: u16, modifiers: KeyModifiers) -> Event {
+    Event::Mouse(MouseEvent {
+        kind: MouseEventKind::Down(MouseButton::Left),
+        column,
+        row: 0,
+        modifiers,
+    })
+}
+
+#[test]
+fn test_shift_click() {
+    let mut s = TextInputState::new();
+    s.set_text("0123456789");
+    render(&mut s, 20);
+
+    s.set_cursor(1, false);
+    let r = handle_events(&mut s, true, &click(4, KeyModifiers::SHIFT));
+    assert_eq!(r, TextOutcome::Changed);
+    assert_eq!(s.selection(), 1..4);
+    assert_eq!(s.anchor(), 1);
+
+    let r = handle_events(&mut s, true, &click(6, KeyModifiers::NONE));
+    assert_eq!(r, TextOutcome::Changed);
+    assert!(!s.has_selection());
+    assert_eq!(s.cursor(), 6);
+}
+
+fn mouse(kind: MouseEventKind, column: u16) -> Event {
+    Event::Mouse(MouseEvent {
+        kind,
+        column,
+        row: 0,
+        modifiers: KeyModifiers::NONE,
+    })
+}
+
+#[test]
+fn test_drag_autoscroll() {
+    let mut s = TextInputState::new();
+    s.set_text("0123456789");
+    render(&mut s, 5);
+
+    handle_events(&mut s, true, &click(1, KeyModifiers::NONE));
+    handle_events(
+        &mut s,
+        true,
+        &mouse(MouseEventKind::Drag(MouseButton::Left), 7),
+    );
+    assert_eq!(s.selection(), 1..7);
+    assert_eq!(s.offset(), 3);
+
+    assert!(s.drag_autoscroll());
+    assert_eq!(s.selection(), 1..10);
+    assert!(!s.drag_autoscroll());
+
+    handle_events(
+        &mut s,
+        true,
+        &mouse(MouseEventKind::Up(MouseButton::Left), 7),
+    );
+    assert_eq!(s.drag_overflow, None);
+}
+
+#[test]
+fn test_inactive_select_style() {
+    let mut s = TextInputState::new();
+    s.set_text("0123456789");
+    s.set_selection(2, 4);
+
+    let area = Rect::new(0, 0, 10, 1);
+    let mut buf = Buffer::empty(area);
+    TextInput::new().render(area, &mut buf, &mut s);
+    assert_eq!(buf[(2, 0)].bg, Color::Reset);
+
+    let mut buf = Buffer::empty(area);
+    TextInput::new()
+        .inactive_select_style(Style::new().bg(Color::Gray))
+        .render(area, &mut buf, &mut s);
+    assert_eq!(buf[(1, 0)].bg, Color::Reset);
+    assert_eq!(buf[(2, 0)].bg, Color::Gray);
+    assert_eq!(buf[(3, 0)].bg, Color::Gray);
+    assert_eq!(buf[(4, 0)].bg, Color::Reset);
+}
+
+#[test]
+fn test_caret_position() {
+    let mut s = TextInputState::new();
+    s.set_text("0123456789");
+    s.set_cursor(3, false);
+
+    let area = Rect::new(5, 2, 10, 1);
+    let mut buf = Buffer::empty(area);
+    TextInput::new().render(area, &mut buf, &mut s);
+
+    assert_eq!(s.caret_position(), Some((8, 2)));
+    assert!(!s.is_caret_visible());
+    assert_eq!(s.screen_cursor(), None);
+
+    s.focus.set(true);
+    assert!(s.is_caret_visible());
+    assert_eq!(s.screen_cursor(), Some((8, 2)));
+}
+
+#[test]
+fn test_stats() {
+    let mut s = TextInputState::new();
+    s.set_text(" a bc  dé ");
+
+    let stats = s.stats();
+    assert_eq!(stats.graphemes, 10);
+    assert_eq!(stats.chars, 10);
+    assert_eq!(stats.bytes, 11);
+    assert_eq!(stats.words, 3);
+    assert_eq!(stats.lines, 1);
+}
+
+fn key(code: KeyCode, modifiers: KeyModifiers) -> Event {
+    Event::Key(KeyEvent::new(code, modifiers))
+}
+
+fn key_release(code: KeyCode, modifiers: KeyModifiers) -> Event {
+    Event::Key(KeyEvent::new_with_kind(
+        code,
+        modifiers,
+        KeyEventKind::Release,
+    ))
+}
+
+#[test]
+fn test_delete_to_line_start_end() {
+    let mut s = TextInputState::new();
+    s.set_text("0123456789");
+    s.set_cursor(4, false);
+
+    let r = handle_events(
+        &mut s,
+        true,
+        &key(KeyCode::Char('k'), KeyModifiers::CONTROL),
+    );
+    assert_eq!(r, TextOutcome::TextChanged);
+    assert_eq!(s.text(), "0123");
+
+    s.set_cursor(2, false);
+    let r = handle_events(
+        &mut s,
+        true,
+        &key(KeyCode::Char('u'), KeyModifiers::CONTROL),
+    );
+    assert_eq!(r, TextOutcome::TextChanged);
+    assert_eq!(s.text(), "23");
+    assert_eq!(s.cursor(), 0);
+
+    let r = handle_events(
+        &mut s,
+        true,
+        &key(KeyCode::Char('u'), KeyModifiers::CONTROL),
+    );
+    assert_eq!(r, TextOutcome::Unchanged);
+    let r = handle_events(
+        &mut s,
+        true,
+        &key_release(KeyCode::Char('u'), KeyModifiers::CONTROL),
+    );
+    assert_eq!(r, TextOutcome::Unchanged);
+}
+
+#[test]
+fn test_paste_newline() {
+    let mut s = TextInputState::new();
+    s.clipboard().unwrap().set_string("a\nb\r\nc").unwrap();
+    assert!(s.paste_from_clip());
+    assert_eq!(s.text(), "a b c");
+
+    s.clear();
+    s.set_truncate_newline(true);
+    assert!(s.paste_from_clip());
+    assert_eq!(s.text(), "a");
+}
+
+#[test]
+fn test_insert_result() {
+    let mut s = TextInputState::new();
+    assert_eq!(
+        s.insert_char_result('a'),
+        InsertResult {
+            inserted: 1,
+            rejected: 0
+        }
+    );
+    assert_eq!(
+        s.insert_char_result('\n'),
+        InsertResult {
+            inserted: 0,
+            rejected: 1
+        }
+    );
+    assert!(!s.insert_char('\n'));
+
+    // a rejected line-break keeps the selection.
+    s.select_all();
+    assert!(!s.insert_char('\n'));
+    assert_eq!(s.text(), "a");
+    assert_eq!(s.selection(), 0..1);
+    s.set_cursor(1, false);
+
+    s.set_truncate_newline(true);
+    assert_eq!(
+        s.insert_str_result("bc\ndef"),
+        InsertResult {
+            inserted: 2,
+            rejected: 4
+        }
+    );
+    assert_eq!(s.text(), "abc");
+}
+
+#[test]
+fn test_cursor_style() {
+    let mut s = TextInputState::new();
+    s.set_text("0123456789");
+    s.focus.set(true);
+    s.set_cursor(2, false);
+
+    let area = Rect::new(0, 0, 20, 1);
+    let mut buf = Buffer::empty(area);
+    TextInput::new()
+        .cursor_style(Style::new().add_modifier(Modifier::REVERSED))
+        .render(area, &mut buf, &mut s);
+    assert_eq!(buf[(2, 0)].symbol(), "2");
+    assert!(buf[(2, 0)].modifier.contains(Modifier::REVERSED));
+    assert!(!buf[(1, 0)].modifier.contains(Modifier::REVERSED));
+
+    // caret after the end of the text
+    s.set_cursor(10, false);
+    let mut buf = Buffer::empty(area);
+    TextInput::new()
+        .cursor_style(Style::new().add_modifier(Modifier::REVERSED))
+        .render(area, &mut buf, &mut s);
+    assert_eq!(buf[(10, 0)].symbol(), " ");
+    assert!(buf[(10, 0)].modifier.contains(Modifier::REVERSED));
+}
+
+#[test]
+fn test_scroll_off() {
+    let mut s = TextInputState::new();
+    s.set_text("012345678901234567890123456789");
+    s.set_scroll_off(3);
+    render(&mut s, 10);
+
+    s.set_cursor(6, false);
+    assert!(!s.scroll_cursor_to_visible());
+    s.set_cursor(8, false);
+    assert!(s.scroll_cursor_to_visible());
+    assert_eq!(s.offset(), 2);
+    s.set_cursor(0, false);
+    assert!(s.scroll_cursor_to_visible());
+    assert_eq!(s.offset(), 0);
+
+    // clamped at the end of the text
+    s.set_cursor(30, false);
+    assert!(s.scroll_cursor_to_visible());
+    assert_eq!(s.offset(), 21);
+    s.set_cursor(22, false);
+    assert!(s.scroll_cursor_to_visible());
+    assert_eq!(s.offset(), 19);
+}
+
+#[test]
+fn test_matching_bracket() {
+    let mut s = TextInputState::new();
+    s.set_text("a(b[c]{d(e)})f(");
+
+    assert_eq!(s.matching_bracket(1), Some(12));
+    assert_eq!(s.matching_bracket(12), Some(1));
+    assert_eq!(s.matching_bracket(3), Some(5));
+    assert_eq!(s.matching_bracket(10), Some(8));
+    assert_eq!(s.matching_bracket(0), None);
+    assert_eq!(s.matching_bracket(14), None);
+
+    s.set_cursor(12, false);
+    assert!(s.select_to_matching_bracket());
+    assert_eq!(s.selection(), 1..13);
+    assert_eq!(s.cursor(), 1);
+
+    s.set_cursor(3, false);
+    assert!(s.delete_to_matching_bracket());
+    assert_eq!(s.text(), "a(b{d(e)})f(");
+
+    s.set_cursor(11, false);
+    assert!(!s.select_to_matching_bracket());
+    assert!(!s.delete_to_matching_bracket());
+}
+
+#[test]
+fn test_try_edit() {
+    let mut s = TextInputState::new();
+    assert_eq!(s.try_insert_char('a'), Ok(true));
+    assert_eq!(s.try_insert_char('\n'), Ok(false));
+    assert_eq!(s.try_insert_str("bc def"), Ok(true));
+    assert_eq!(s.try_move_to_prev_word(false), Ok(true));
+    assert_eq!(s.cursor(), 4);
+    assert_eq!(s.try_delete_prev_char(), Ok(TextOutcome::TextChanged));
+    assert_eq!(s.text(), "abcdef");
+    assert_eq!(s.try_move_right(false), Ok(true));
+    assert_eq!(s.try_move_to_line_end(false), Ok(true));
+    assert_eq!(s.try_move_right(false), Ok(false));
+    assert_eq!(s.try_delete_prev_word(), Ok(true));
+    assert_eq!(s.text(), "");
+}
+
+#[test]
+fn test_null_clipboard() {
+    let mut s = TextInputState::new();
+    s.set_clipboard(Some(NullClipboard::new()));
+    s.set_text("abc");
+    s.select_all();
+
+    s.copy_to_clip();
+    assert!(s.cut_to_clip());
+    assert_eq!(s.text(), "");
+    assert!(s.paste_from_clip());
+    assert_eq!(s.text(), "");
+}
+
+#[test]
+fn test_copy_html() {
+    let mut s = TextInputState::new();
+    s.set_text("a<b> cd ef");
+    s.add_range_style(1..4, 0).unwrap();
+    s.add_range_style(3..7, 1).unwrap();
+    s.set_selection(0, 9);
+
+    s.copy_to_clip();
+    let clip = s.clipboard().unwrap();
+    assert_eq!(clip.get_string().unwrap(), "a<b> cd e");
+    assert_eq!(
+        clip.get_html().unwrap(),
+        "a<span class=\"style-0\">&lt;b</span>\
+        <span class=\"style-0 style-1\">&gt;</span>\
+        <span class=\"style-1\"> cd</span> e"
+    );
+}
+
+#[test]
+fn test_char_remap() {
+    let mut s = TextInputState::new();
+    s.set_char_remap(Some(|c| match c {
+        ',' => Some('.'),
+        'x' => None,
+        c => Some(c),
+    }));
+
+    assert!(s.insert_char('1'));
+    assert!(s.insert_char(','));
+    assert!(!s.insert_char('x'));
+    assert!(s.insert_char('5'));
+    assert_eq!(s.text(), "1.5");
+}
+
+#[test]
+fn test_set_text_keep_cursor() {
+    let mut s = TextInputState::new();
+    s.set_text("0123456789");
+    render(&mut s, 5);
+    s.set_cursor(8, false);
+    s.scroll_cursor_to_visible();
+    assert_eq!(s.offset(), 4);
+
+    s.set_text_keep_cursor("0123456789ab");
+    assert_eq!(s.cursor(), 8);
+    assert_eq!(s.offset(), 4);
+
+    s.set_text_keep_cursor("012345");
+    assert_eq!(s.cursor(), 6);
+    assert_eq!(s.offset(), 4);
+
+    s.set_text("0123456789");
+    assert_eq!(s.cursor(), 6);
+    assert_eq!(s.offset(), 0);
+}
+
+#[test]
+fn test_insert_graphemes() {
+    let mut s = TextInputState::new();
+    s.set_text("ab");
+    s.set_selection(1, 2);
+
+    assert_eq!(s.insert_graphemes(["x", "e\u{301}", "日"]), 3);
+    assert_eq!(s.text(), "axe\u{301}日");
+    assert_eq!(s.cursor(), 4);
+
+    assert!(s.try_insert_graphemes(["y", "zz"]).is_err());
+    assert!(s.try_insert_graphemes(["\n"]).is_err());
+    assert_eq!(s.text(), "axe\u{301}日");
+
+    // one undo step
+    s.undo();
+    assert_eq!(s.text(), "ab");
+}
+
+#[test]
+fn test_preedit() {
+    let mut s = TextInputState::new();
+    s.set_text("abcd");
+    s.set_cursor(2, false);
+    s.focus.set(true);
+    s.set_preedit(Some("日本".into()));
+    s.set_preedit_cursor(1);
+
+    let buf = render(&mut s, 10);
+    assert_eq!(buf[(0, 0)].symbol(), "a");
+    assert_eq!(buf[(1, 0)].symbol(), "b");
+    assert_eq!(buf[(2, 0)].symbol(), "日");
+    assert!(buf[(2, 0)].modifier.contains(Modifier::UNDERLINED));
+    assert_eq!(buf[(4, 0)].symbol(), "本");
+    assert_eq!(buf[(6, 0)].symbol(), "c");
+    assert_eq!(buf[(7, 0)].symbol(), "d");
+    assert_eq!(s.text(), "abcd");
+    assert_eq!(s.screen_cursor(), Some((4, 0)));
+
+    // commit
+    s.insert_str("日本");
+    s.set_preedit(None);
+    let buf = render(&mut s, 10);
+    assert_eq!(s.text(), "ab日本cd");
+    assert_eq!(buf[(6, 0)].symbol(), "c");
+    assert!(!buf[(2, 0)].modifier.contains(Modifier::UNDERLINED));
+    assert_eq!(s.screen_cursor(), Some((6, 0)));
+}
+
+#[test]
+fn test_tab_action() {
+    let mut s = TextInputState::new();
+    s.focus.set(true);
+
+    let r = handle_events(&mut s, true, &key(KeyCode::Tab, KeyModifiers::NONE));
+    assert_eq!(r, TextOutcome::TextChanged);
+    assert_eq!(s.text(), "\t");
+
+    s.set_tab_action(TabAction::FocusNext);
+    let r = handle_events(&mut s, true, &key(KeyCode::Tab, KeyModifiers::NONE));
+    assert_eq!(r, TextOutcome::Continue);
+    let r = handle_events(&mut s, true, &key_release(KeyCode::Tab, KeyModifiers::NONE));
+    assert_eq!(r, TextOutcome::Continue);
+    assert_eq!(s.text(), "\t");
+
+    s.set_tab_action(TabAction::Insert);
+    let r = handle_events(&mut s, true, &key(KeyCode::Tab, KeyModifiers::NONE));
+    assert_eq!(r, TextOutcome::TextChanged);
+    assert_eq!(s.text(), "\t\t");
+}
+
+#[test]
+fn test_selection_direction() {
+    let mut s = TextInputState::new();
+    s.set_text("0123456789");
+    assert_eq!(s.selection_direction(), None);
+
+    s.set_selection(2, 5);
+    assert_eq!(s.selection(), 2..5);
+    assert_eq!(s.selection_direction(), Some(Ordering::Less));
+
+    s.set_selection(5, 2);
+    assert_eq!(s.selection(), 2..5);
+    assert_eq!(s.selection_direction(), Some(Ordering::Greater));
+}
+
+#[test]
+fn test_move_by() {
+    let mut s = TextInputState::new();
+    s.set_text("0123456789012345678901234567890123456789");
+    render(&mut s, 10);
+
+    assert!(s.move_right_by(15, false));
+    assert_eq!(s.cursor(), 15);
+    assert!(s.move_left_by(20, true));
+    assert_eq!(s.cursor(), 0);
+    assert_eq!(s.selection(), 0..15);
+    assert!(s.move_left_by(1, false));
+    assert!(!s.has_selection());
+    assert!(!s.move_left_by(1, false));
+
+    s.focus.set(true);
+    s.set_cursor(0, false);
+    let r = handle_events(&mut s, true, &key(KeyCode::PageDown, KeyModifiers::NONE));
+    assert_eq!(r, TextOutcome::Changed);
+    assert_eq!(s.cursor(), 10);
+    handle_events(&mut s, true, &key(KeyCode::PageDown, KeyModifiers::NONE));
+    handle_events(&mut s, true, &key(KeyCode::PageDown, KeyModifiers::NONE));
+    handle_events(&mut s, true, &key(KeyCode::PageDown, KeyModifiers::NONE));
+    handle_events(&mut s, true, &key(KeyCode::PageDown, KeyModifiers::NONE));
+    assert_eq!(s.cursor(), 40);
+    handle_events(&mut s, true, &key(KeyCode::PageUp, KeyModifiers::SHIFT));
+    assert_eq!(s.cursor(), 30);
+    assert_eq!(s.selection(), 30..40);
+}
+
+#[test]
+fn test_undo_styles() {
+    let mut s = TextInputState::new();
+    s.set_text("abc");
+    assert!(!s.undo_styles());
+
+    s.add_style(0..1, 1);
+    assert!(!s.undo());
+    assert_eq!(s.styles().expect("styles").count(), 1);
+
+    s.set_undo_styles(true);
+    assert!(s.undo_styles());
+    s.add_style(1..2, 2);
+    assert_eq!(s.styles().expect("styles").count(), 2);
+    assert!(s.undo());
+    assert_eq!(
+        s.styles().expect("styles").collect::<Vec<_>>(),
+        vec![(0..1, 1)]
+    );
+}
+
+#[test]
+fn test_display_truncated() {
+    let mut s = TextInputState::new();
+    s.set_text("abcdef");
+    assert_eq!(s.display_truncated(6, "…"), "abcdef");
+    assert_eq!(s.display_truncated(5, "…"), "abcd…");
+    assert_eq!(s.display_truncated(5, "..."), "ab...");
+    assert_eq!(s.display_truncated(2, "..."), "...");
+
+    s.set_text("a日本b");
+    assert_eq!(s.display_truncated(6, "…"), "a日本b");
+    assert_eq!(s.display_truncated(5, "…"), "a日…");
+    assert_eq!(s.display_truncated(4, "…"), "a日…");
+    assert_eq!(s.display_truncated(3, "…"), "a…");
+}
+
+#[test]
+fn test_style_spans() {
+    let mut s = TextInputState::new();
+    s.set_text("abcdefgh");
+    s.add_style(1..3, 1);
+    s.add_style(2..5, 2);
+
+    assert_eq!(
+        s.style_spans(0..8).collect::<Vec<_>>(),
+        vec![
+            (0..1, vec![]),
+            (1..2, vec![1]),
+            (2..3, vec![1, 2]),
+            (3..5, vec![2]),
+            (5..8, vec![]),
+        ]
+    );
+    assert_eq!(
+        s.style_spans(3..6).collect::<Vec<_>>(),
+        vec![(3..5, vec![2]), (5..6, vec![])]
+    );
+}
+
+#[test]
+fn test_drag_word_granularity() {
+    let mut s = TextInputState::new();
+    s.set_text("alpha beta gamma");
+    render(&mut s, 20);
+
+    handle_events(&mut s, true, &click(2, KeyModifiers::NONE));
+    assert_eq!(s.selection_granularity, SelectionGranularity::Char);
+    // as if started with a double-click
+    s.selection_granularity = SelectionGranularity::Word;
+    handle_events(
+        &mut s,
+        true,
+        &mouse(MouseEventKind::Drag(MouseButton::Left), 8),
+    );
+    assert_eq!(s.selection(), 0..10);
+
+    handle_events(
+        &mut s,
+        true,
+        &mouse(MouseEventKind::Up(MouseButton::Left), 8),
+    );
+    handle_events(&mut s, true, &click(12, KeyModifiers::NONE));
+    assert_eq!(s.selection_granularity, SelectionGranularity::Char);
+    handle_events(
+        &mut s,
+        true,
+        &mouse(MouseEventKind::Drag(MouseButton::Left), 14),
+    );
+    assert_eq!(s.selection(), 12..14);
+}
+
+#[test]
+fn test_caret_width() {
+    let mut s = TextInputState::new();
+    s.set_text("a日b");
+    render(&mut s, 10);
+
+    s.set_cursor(0, false);
+    assert_eq!(s.caret_width(), 1);
+    s.set_cursor(1, false);
+    assert_eq!(s.caret_width(), 2);
+    s.set_cursor(3, false);
+    assert_eq!(s.caret_width(), 1);
+}
+
+#[test]
+fn test_delete_byte_range() {
+    let mut s = TextInputState::new();
+    s.set_text("aöb日c");
+
+    assert_eq!(s.delete_byte_range(1..3), Ok(true));
+    assert_eq!(s.text(), "ab日c");
+    assert!(s.delete_byte_range(2..3).is_err());
+    assert!(s.delete_byte_range(0..10).is_err());
+    assert_eq!(s.delete_byte_range(2..2), Ok(false));
+    assert_eq!(s.delete_byte_range(2..5), Ok(true));
+    assert_eq!(s.text(), "abc");
+}
+
+#[test]
+fn test_extra_cursors() {
+    let mut s = TextInputState::new();
+    s.set_text("ab cd ef");
+    s.set_cursor(2, false);
+    assert!(s.add_cursor(5));
+    assert!(s.add_cursor(0));
+    assert!(!s.add_cursor(2));
+    assert!(!s.add_cursor(5));
+    assert_eq!(s.extra_cursors(), &[0, 5]);
+
+    s.focus.set(true);
+    let buf = render(&mut s, 10);
+    assert!(buf[(0, 0)].modifier.contains(Modifier::REVERSED));
+    assert!(buf[(5, 0)].modifier.contains(Modifier::REVERSED));
+    assert!(!buf[(2, 0)].modifier.contains(Modifier::REVERSED));
+
+    s.insert_char('x');
+    assert_eq!(s.text(), "xabx cdx ef");
+    assert_eq!(s.cursor(), 4);
+    assert_eq!(s.extra_cursors(), &[1, 8]);
+
+    // one undo step, the carets are gone with it.
+    s.undo();
+    assert_eq!(s.text(), "ab cd ef");
+    assert_eq!(s.extra_cursors(), &[] as &[u32]);
+
+    assert!(s.add_cursor(3));
+    assert!(s.clear_extra_cursors());
+    assert!(!s.clear_extra_cursors());
+}
+
+#[test]
+fn test_extra_cursors_other_edits() {
+    // typing over a selection
+    let mut s = TextInputState::new();
+    s.set_text("ab cd ef");
+    s.set_selection(0, 3);
+    s.add_cursor(6);
+    s.add_cursor(8);
+    s.insert_char('x');
+    assert_eq!(s.text(), "xcd ef");
+    assert_eq!(s.cursor(), 1);
+    assert_eq!(s.extra_cursors(), &[] as &[u32]);
+    s.insert_char('y');
+    assert_eq!(s.text(), "xycd ef");
+
+    // deleting a selection
+    s.set_selection(0, 2);
+    s.add_cursor(5);
+    assert_eq!(s.delete_next_char(), TextOutcome::TextChanged);
+    assert_eq!(s.text(), "cd ef");
+    assert_eq!(s.extra_cursors(), &[] as &[u32]);
+
+    // backspace and delete
+    s.set_cursor(2, false);
+    s.add_cursor(4);
+    s.delete_prev_char();
+    assert_eq!(s.extra_cursors(), &[] as &[u32]);
+    s.add_cursor(4);
+    s.delete_next_char();
+    assert_eq!(s.extra_cursors(), &[] as &[u32]);
+    assert_eq!(s.text(), "cef");
+
+    // other inserts
+    s.add_cursor(0);
+    s.insert_str("zz");
+    assert_eq!(s.extra_cursors(), &[] as &[u32]);
+    s.add_cursor(0);
+    s.set_text("abc");
+    assert_eq!(s.extra_cursors(), &[] as &[u32]);
+}
+
+#[test]
+fn test_display_only() {
+    let mut s = TextInputState::display_only();
+    assert!(s.read_only());
+    assert!(s.undo_buffer().is_none());
+    assert!(s.clipboard().is_none());
+
+    s.set_text("abc");
+    s.focus.set(true);
+    let r = handle_events(&mut s, true, &key(KeyCode::Char('x'), KeyModifiers::NONE));
+    assert_eq!(r, TextOutcome::Continue);
+    assert_eq!(s.text(), "abc");
+    let r = handle_events(&mut s, true, &key(KeyCode::Right, KeyModifiers::NONE));
+    assert_eq!(r, TextOutcome::Changed);
+
+    s.insert_char('x');
+    assert!(!s.undo());
+    assert!(!s.paste_from_clip());
+}
+
+#[test]
+fn test_total_display_width() {
+    let mut s = TextInputState::new();
+    assert_eq!(s.total_display_width(), 0);
+    s.set_text("a日本b");
+    assert_eq!(s.len(), 4);
+    assert_eq!(s.total_display_width(), 6);
+    s.set_text("ab\tc");
+    assert_eq!(s.total_display_width(), 9);
+
+    s.set_text("x".repeat(70000));
+    assert_eq!(s.total_display_width(), u16::MAX);
+}
+
+#[test]
+fn test_paste_over_all() {
+    let mut s = TextInputState::new();
+    s.set_text("0123456789012345678901234567890123456789");
+    render(&mut s, 10);
+    s.set_cursor(35, false);
+    s.scroll_cursor_to_visible();
+    assert!(s.offset() > 0);
+
+    s.select_all();
+    s.insert_str("abc");
+    assert_eq!(s.text(), "abc");
+    assert_eq!(s.offset(), 0);
+    let buf = render(&mut s, 10);
+    assert_eq!(buf[(0, 0)].symbol(), "a");
+    assert_eq!(buf[(2, 0)].symbol(), "c");
+}
+
+#[test]
+fn test_dirty() {
+    let mut s = TextInputState::new();
+    assert!(!s.is_dirty());
+
+    s.set_text("abc");
+    assert!(s.is_dirty());
+    s.mark_clean();
+    assert!(!s.is_dirty());
+
+    s.insert_char('d');
+    assert!(s.is_dirty());
+    s.delete_prev_char();
+    assert!(!s.is_dirty());
+}
+
+#[test]
+fn test_char_around_cursor() {
+    let mut s = TextInputState::new();
+    s.set_text("a日b");
+
+    s.set_cursor(0, false);
+    assert_eq!(s.char_before_cursor(), None);
+    assert_eq!(s.char_after_cursor(), Some("a"));
+    s.set_cursor(1, false);
+    assert_eq!(s.char_before_cursor(), Some("a"));
+    assert_eq!(s.char_after_cursor(), Some("日"));
+    s.set_cursor(3, false);
+    assert_eq!(s.char_before_cursor(), Some("b"));
+    assert_eq!(s.char_after_cursor(), None);
+}
+
+#[test]
+fn test_smart_backspace() {
+    let mut s = TextInputState::new();
+    s.value.set_tab_width(4);
+    s.set_text("ab      x");
+    s.set_smart_backspace(true);
+
+    s.set_cursor(8, false);
+    assert_eq!(s.delete_prev_char(), TextOutcome::TextChanged);
+    assert_eq!(s.text(), "ab  x");
+    assert_eq!(s.cursor(), 4);
+    // not only spaces
+    assert_eq!(s.delete_prev_char(), TextOutcome::TextChanged);
+    assert_eq!(s.text(), "ab x");
+
+    s.undo();
+    assert_eq!(s.text(), "ab  x");
+    s.undo();
+    assert_eq!(s.text(), "ab      x");
+
+    s.set_smart_backspace(false);
+    s.set_cursor(8, false);
+    assert_eq!(s.delete_prev_char(), TextOutcome::TextChanged);
+    assert_eq!(s.text(), "ab     x");
+}
+
+#[test]
+fn test_tab_width() {
+    let mut s = TextInputState::new();
+    s.set_tab_width(4);
+    assert_eq!(s.tab_width(), 4);
+    assert!(s.expand_tabs());
+
+    s.set_text("ab");
+    s.set_cursor(2, false);
+    assert!(s.insert_tab());
+    assert_eq!(s.text(), "ab  ");
+
+    s.set_expand_tabs(false);
+    assert!(!s.expand_tabs());
+    assert!(s.insert_tab());
+    assert_eq!(s.text(), "ab  \t");
+    assert_eq!(s.total_display_width(), 8);
+}
+
+#[test]
+fn test_show_ctrl() {
+    let mut s = TextInputState::new();
+    s.set_text("a\tb");
+
+    let area = Rect::new(0, 0, 20, 1);
+    let mut buf = Buffer::empty(area);
+    TextInput::new()
+        .show_ctrl(true)
+        .ctrl_style(Style::new().fg(Color::Red))
+        .render(area, &mut buf, &mut s);
+    assert!(s.show_ctrl());
+    assert_eq!(buf[(0, 0)].symbol(), "a");
+    assert_eq!(buf[(0, 0)].fg, Color::Reset);
+    assert_eq!(buf[(1, 0)].symbol(), "\u{2409}");
+    assert_eq!(buf[(1, 0)].fg, Color::Red);
+    assert_eq!(buf[(8, 0)].symbol(), "b");
+
+    s.set_show_ctrl(false);
+    let mut buf = Buffer::empty(area);
+    TextInput::new()
+        .ctrl_style(Style::new().fg(Color::Red))
+        .render(area, &mut buf, &mut s);
+    assert_eq!(buf[(1, 0)].symbol(), " ");
+    assert_eq!(buf[(1, 0)].fg, Color::Reset);
+}
+
+#[test]
+fn test_selected_spans() {
+    let mut s = TextInputState::new();
+    s.set_text("abcdefgh");
+    s.add_style(1..3, 1);
+    assert_eq!(s.selected_spans(), vec![]);
+
+    s.set_selection(2, 5);
+    assert_eq!(
+        s.selected_spans(),
+        vec![("c".to_string(), vec![1]), ("de".to_string(), vec![])]
+    );
+}
+
+#[test]
+fn test_delete_outcome() {
+    let mut s = TextInputState::new();
+    s.set_text("0123456789");
+    render(&mut s, 5);
+
+    s.set_cursor(10, false);
+    assert_eq!(s.delete_next_char(), TextOutcome::Unchanged);
+    assert_eq!(s.delete_prev_char(), TextOutcome::TextChanged);
+    assert_eq!(s.text(), "012345678");
+
+    // cursor out of view, only scrolls.
+    s.set_cursor(0, false);
+    s.set_offset(4);
+    assert_eq!(s.delete_prev_char(), TextOutcome::Changed);
+    assert_eq!(s.offset(), 0);
+    assert_eq!(s.text(), "012345678");
+    assert_eq!(s.delete_prev_char(), TextOutcome::Unchanged);
+}
+
+#[test]
+fn test_outcome_scroll_vs_edit() {
+    assert_eq!(TextOutcome::edit(true, true), TextOutcome::TextChanged);
+    assert_eq!(TextOutcome::edit(false, true), TextOutcome::Changed);
+    assert_eq!(TextOutcome::edit(false, false), TextOutcome::Unchanged);
+
+    let mut s = TextInputState::new();
+    s.set_text("0123456789");
+    render(&mut s, 5);
+
+    let r = handle_events(&mut s, true, &key(KeyCode::Left, KeyModifiers::ALT));
+    assert_eq!(r, TextOutcome::Unchanged);
+    let r = handle_events(&mut s, true, &key(KeyCode::Right, KeyModifiers::ALT));
+    assert_eq!(r, TextOutcome::Changed);
+    let r = handle_events(&mut s, true, &key(KeyCode::End, KeyModifiers::NONE));
+    assert_eq!(r, TextOutcome::Changed);
+    let r = handle_events(&mut s, true, &key(KeyCode::Backspace, KeyModifiers::NONE));
+    assert_eq!(r, TextOutcome::TextChanged);
+}
+
+#[test]
+fn test_from_text() {
+    let s = TextInputState::from_text("abc");
+    assert_eq!(s.text(), "abc");
+    assert_eq!(s.cursor(), 0);
+    assert_eq!(s.offset(), 0);
+
+    let s = TextInputState::from_text_cursor_end("abc");
+    assert_eq!(s.cursor(), 3);
+    assert_eq!(s.offset(), 0);
+
+    let s = TextInputState::named_with_text("name", "abc");
+    assert_eq!(s.text(), "abc");
+    assert_eq!(s.cursor(), 0);
+}
+
+#[test]
+fn test_render_clipped() {
+    let mut s = TextInputState::new();
+    s.set_text("0123456789");
+    s.focus.set(true);
+    s.set_cursor(8, false);
+
+    let mut buf = Buffer::empty(Rect::new(0, 0, 10, 1));
+    TextInput::new()
+        .cursor_style(Style::new().add_modifier(Modifier::REVERSED))
+        .render(Rect::new(5, 0, 10, 1), &mut buf, &mut s);
+    assert_eq!(buf[(5, 0)].symbol(), "0");
+    assert_eq!(buf[(9, 0)].symbol(), "4");
+
+    let mut buf = Buffer::empty(Rect::new(0, 0, 10, 1));
+    TextInput::new().render(Rect::new(20, 5, 10, 1), &mut buf, &mut s);
+    assert_eq!(buf[(0, 0)].symbol(), " ");
+}
+
+#[test]
+fn test_readonly_copy() {
+    let mut s = TextInputState::new();
+    s.set_text("abcdef");
+
+    let r = handle_readonly_events(
+        &mut s,
+        true,
+        &key(KeyCode::Char('a'), KeyModifiers::CONTROL),
+    );
+    assert_eq!(r, TextOutcome::Changed);
+    let r = handle_readonly_events(
+        &mut s,
+        true,
+        &key(KeyCode::Char('c'), KeyModifiers::CONTROL),
+    );
+    assert_ne!(r, TextOutcome::Continue);
+    assert_eq!(s.clipboard().unwrap().get_string().unwrap(), "abcdef");
+
+    // no cut or paste
+    let r = handle_readonly_events(
+        &mut s,
+        true,
+        &key(KeyCode::Char('x'), KeyModifiers::CONTROL),
+    );
+    assert_eq!(r, TextOutcome::Continue);
+    let r = handle_readonly_events(
+        &mut s,
+        true,
+        &key(KeyCode::Char('v'), KeyModifiers::CONTROL),
+    );
+    assert_eq!(r, TextOutcome::Continue);
+    assert_eq!(s.text(), "abcdef");
+}
+
+#[test]
+fn test_on_reject() {
+    let count = Rc::new(Cell::new(0));
+    let mut s = TextInputState::new();
+    s.set_on_reject(Some(Rc::new({
+        let count = count.clone();
+        move || count.set(count.get() + 1)
+    })));
+
+    s.insert_char('a');
+    assert_eq!(count.get(), 0);
+    s.insert_char('\n');
+    assert_eq!(count.get(), 1);
+
+    s.set_truncate_newline(true);
+    s.insert_str("b\nc");
+    assert_eq!(count.get(), 2);
+    assert_eq!(s.text(), "ab");
+
+    // boundary no-ops don't count
+    s.set_cursor(2, false);
+    s.delete_next_char();
+    assert_eq!(count.get(), 2);
+}
+
+#[test]
+fn test_newline_marker() {
+    let mut s = TextInputState::new();
+    s.insert_str("a\nb");
+    assert_eq!(s.text(), "a b");
+    assert_eq!(s.text_expanded(), "a b");
+
+    let mut s = TextInputState::new();
+    s.set_newline_marker(Some('␊'));
+    s.set_truncate_newline(true);
+    let r = s.insert_str_result("a\r\nb\nc");
+    assert_eq!(r.rejected, 0);
+    assert_eq!(s.text(), "a␊b␊c");
+    assert_eq!(s.text_expanded(), "a\nb\nc");
+    assert_eq!(s.cursor(), 5);
+}
+
+#[test]
+fn test_display_offset() {
+    let mut s = TextInputState::new();
+    s.set_text("a日本b");
+    assert_eq!(s.display_offset(), 0);
+
+    assert!(s.scroll_right_display(1));
+    assert_eq!(s.offset(), 1);
+    assert_eq!(s.display_offset(), 1);
+
+    // wide graphemes are not split
+    assert!(s.scroll_right_display(1));
+    assert_eq!(s.offset(), 2);
+    assert_eq!(s.display_offset(), 3);
+    assert!(s.scroll_right_display(3));
+    assert_eq!(s.offset(), 4);
+    assert_eq!(s.display_offset(), 6);
+    assert!(!s.scroll_right_display(1));
+
+    assert!(s.scroll_left_display(2));
+    assert_eq!(s.offset(), 2);
+    assert_eq!(s.display_offset(), 3);
+    assert!(s.scroll_left_display(1));
+    assert_eq!(s.offset(), 1);
+    assert!(s.scroll_left_display(5));
+    assert_eq!(s.offset(), 0);
+    assert!(!s.scroll_left_display(1));
+
+    // grapheme based
+    assert!(s.scroll_right(2));
+    assert_eq!(s.offset(), 2);
+    assert_eq!(s.display_offset(), 3);
+}
+
+#[test]
+fn test_render_empty_focused() {
+    let mut s = TextInputState::new();
+    s.focus.set(true);
+
+    let area = Rect::new(0, 0, 12, 3);
+    let mut buf = Buffer::empty(area);
+    TextInput::new()
+        .block(Block::bordered())
+        .focus_style(Style::new().bg(Color::Blue))
+        .cursor_style(Style::new().add_modifier(Modifier::REVERSED))
+        .render(area, &mut buf, &mut s);
+
+    assert_eq!(s.inner, Rect::new(1, 1, 10, 1));
+    assert_eq!(s.screen_cursor(), Some((1, 1)));
+    assert_eq!(buf[(1, 1)].symbol(), " ");
+    assert_eq!(buf[(1, 1)].bg, Color::Blue);
+    assert!(buf[(1, 1)].modifier.contains(Modifier::REVERSED));
+    assert_eq!(buf[(2, 1)].bg, Color::Blue);
+    assert!(!buf[(2, 1)].modifier.contains(Modifier::REVERSED));
+}
+
+#[test]
+fn test_wide_char_right_edge() {
+    let mut s = TextInputState::new();
+    s.set_text("abc日");
+    s.set_cursor(0, false);
+
+    let area = Rect::new(0, 0, 6, 3);
+    let mut buf = Buffer::empty(area);
+    TextInput::new()
+        .block(Block::bordered())
+        .render(area, &mut buf, &mut s);
+
+    assert_eq!(s.inner, Rect::new(1, 1, 4, 1));
+    assert_eq!(buf[(3, 1)].symbol(), "c");
+    // the wide glyph doesn't fit and is replaced within inner.
+    assert_ne!(buf[(4, 1)].symbol(), "日");
+    assert_eq!(buf[(5, 1)].symbol(), "│");
+}
+
+#[test]
+fn test_caret_right_edge() {
+    let mut s = TextInputState::new();
+    s.set_text("0123456789");
+    s.focus.set(true);
+    render(&mut s, 5);
+
+    // caret at the last visible column
+    s.set_cursor(4, false);
+    assert!(!s.scroll_cursor_to_visible());
+    assert_eq!(s.screen_cursor(), Some((4, 0)));
+
+    // one past the last column is not visible
+    s.set_cursor(5, false);
+    assert_eq!(s.screen_cursor(), None);
+    assert!(s.scroll_cursor_to_visible());
+    assert_eq!(s.offset(), 1);
+    assert_eq!(s.screen_cursor(), Some((4, 0)));
+
+    // caret after the end of the text
+    s.set_cursor(10, false);
+    assert!(s.scroll_cursor_to_visible());
+    assert_eq!(s.offset(), 6);
+    assert_eq!(s.screen_cursor(), Some((4, 0)));
+
+    let area = Rect::new(0, 0, 5, 1);
+    let mut buf = Buffer::empty(area);
+    TextInput::new()
+        .cursor_style(Style::new().add_modifier(Modifier::REVERSED))
+        .render(area, &mut buf, &mut s);
+    assert_eq!(buf[(3, 0)].symbol(), "9");
+    assert!(buf[(4, 0)].modifier.contains(Modifier::REVERSED));
+}
+
+#[test]
+fn test_preferred_width() {
+    let mut s = TextInputState::new();
+    assert_eq!(s.preferred_width(20), 1);
+    s.set_text("a日b");
+    assert_eq!(s.preferred_width(20), 5);
+    assert_eq!(s.preferred_width(3), 3);
+    assert_eq!(s.preferred_width(0), 0);
+}
+
+#[test]
+fn test_clear_undo() {
+    let mut s = TextInputState::new();
+    s.focus.set(true);
+    s.set_text("abc");
+    s.set_cursor(2, false);
+
+    assert!(s.clear());
+    assert_eq!(s.text(), "");
+    assert_eq!(s.cursor(), 0);
+    assert!(!s.clear());
+
+    s.undo();
+    assert_eq!(s.text(), "abc");
+
+    // no default keybinding
+    let r = handle_events(
+        &mut s,
+        true,
+        &key(KeyCode::Char('d'), KeyModifiers::CONTROL),
+    );
+    assert_eq!(r, TextOutcome::Continue);
+    assert_eq!(s.text(), "abc");
+}
+
+#[test]
+fn test_try_set_cursor() {
+    let mut s = TextInputState::new();
+    s.set_text("abc");
+    assert_eq!(s.try_set_cursor(3, false), Ok(true));
+    assert_eq!(s.try_set_cursor(3, false), Ok(false));
+    assert!(s.try_set_cursor(4, false).is_err());
+    assert_eq!(s.cursor(), 3);
+
+    // set_cursor caps the position
+    s.set_cursor(0, false);
+    assert!(s.set_cursor(10, false));
+    assert_eq!(s.cursor(), 3);
+}
+
+#[test]
+fn test_reject_leading_combining() {
+    let mut s = TextInputState::new();
+    assert!(s.insert_char('\u{301}'));
+    assert_eq!(s.text(), "\u{301}");
+
+    let mut s = TextInputState::new();
+    s.set_reject_leading_combining(true);
+    assert!(!s.insert_char('\u{301}'));
+    assert_eq!(s.text(), "");
+
+    let r = s.insert_str_result("\u{301}\u{200d}e\u{301}");
+    assert_eq!(r.inserted, 2);
+    assert_eq!(r.rejected, 2);
+    assert_eq!(s.text(), "e\u{301}");
+
+    // after a base character
+    assert!(s.insert_char('\u{302}'));
+    assert_eq!(s.text(), "e\u{301}\u{302}");
+
+    // after a tab
+    s.set_expand_tabs(false);
+    s.insert_tab();
+    assert!(!s.insert_char('\u{301}'));
+}
+
+#[test]
+fn test_len_bytes_chars() {
+    let mut s = TextInputState::new();
+    assert_eq!(s.len_bytes(), 0);
+    assert_eq!(s.len_chars(), 0);
+
+    s.set_text("ae\u{301}日");
+    assert_eq!(s.len(), 3);
+    assert_eq!(s.len_chars(), 4);
+    assert_eq!(s.len_bytes(), 7);
+
+    // the char count follows the edits.
+    s.set_cursor(3, false);
+    s.insert_str("o\u{308}x");
+    assert_eq!(s.len_chars(), 7);
+    s.delete_prev_char();
+    assert_eq!(s.len_chars(), 6);
+    s.delete_range(0..2);
+    assert_eq!(s.len_chars(), 3);
+    s.undo();
+    assert_eq!(s.len_chars(), 6);
+    assert_eq!(s.len_chars(), s.text().chars().count());
+}
+
+#[test]
+fn test_max_bytes() {
+    let mut s = TextInputState::new();
+    s.set_max_bytes(Some(6));
+
+    // 3 byte graphemes at the boundary
+    let r = s.insert_str_result("a日本");
+    assert_eq!(r.inserted, 2);
+    assert_eq!(r.rejected, 1);
+    assert_eq!(s.text(), "a日");
+    assert_eq!(s.len_bytes(), 4);
+
+    // multi-char grapheme isn't split
+    let r = s.insert_str_result("e\u{301}");
+    assert_eq!(r.inserted, 0);
+    assert_eq!(r.rejected, 2);
+    assert!(!s.insert_char('日'));
+    assert!(s.insert_char('ö'));
+    assert_eq!(s.len_bytes(), 6);
+    assert!(!s.insert_char('x'));
+
+    // replacing the selection frees its bytes
+    s.set_selection(1, 2);
+    assert!(s.insert_char('本'));
+    assert_eq!(s.text(), "a本ö");
+
+    s.set_selection(0, 1);
+    assert_eq!(s.insert_graphemes(["x", "y"]), 1);
+    assert_eq!(s.text(), "x本ö");
+}
+
+#[test]
+fn test_undo_len() {
+    let mut s = TextInputState::new();
+    assert_eq!(s.undo_len(), 0);
+    assert_eq!(s.redo_len(), 0);
+
+    s.undo_buffer_mut().expect("undo").enable_replay_log(true);
+    s.insert_str("abc");
+    s.insert_str("def");
+    let n = s.undo_len();
+    assert!(n > 0);
+    assert!(s.undo());
+    assert!(s.undo_len() < n);
+    assert!(s.redo_len() > 0);
+
+    s.clear_undo();
+    assert_eq!(s.text(), "abc");
+    assert_eq!(s.undo_len(), 0);
+    assert_eq!(s.redo_len(), 0);
+    assert!(!s.undo());
+    assert!(!s.recent_replay_log().is_empty());
+
+    let mut s = TextInputState::display_only();
+    s.clear_undo();
+    assert_eq!(s.undo_len(), 0);
+}
+
+#[test]
+fn test_replay_log_checked() {
+    let mut s = TextInputState::new();
+    s.set_text("abcdef");
+    s.undo_buffer_mut().expect("undo").enable_replay_log(true);
+    let mut s2 = s.clone();
+    let mut s3 = s.clone();
+
+    s.set_cursor(6, false);
+    s.insert_str("gh");
+    s.delete_range(0..2);
+    let replay = s.recent_replay_log();
+
+    assert_eq!(s2.replay_log_checked(&replay), Ok(()));
+    assert_eq!(s2.text(), "cdefgh");
+
+    // diverged, the insert still applies, the remove doesn't.
+    s3.delete_range(0..1);
+    s3.set_cursor(0, false);
+    s3.insert_char('X');
+    assert!(matches!(
+        s3.replay_log_checked(&replay),
+        Err(TextError::ReplayConflict(_))
+    ));
+    assert_eq!(s3.text(), "Xbcdefgh");
+}
+
+#[test]
+fn test_set_text_with_styles() {
+    let mut s = TextInputState::new();
+    s.set_undo_styles(true);
+    s.set_text("abc");
+    s.add_style(0..1, 1);
+
+    s.set_text_with_styles("cmd arg", vec![(0..3, 1), (4..7, 2)]);
+    assert_eq!(s.text(), "cmd arg");
+    assert_eq!(
+        s.styles().expect("styles").collect::<Vec<_>>(),
+        vec![(0..3, 1), (4..7, 2)]
+    );
+    assert_eq!(s.undo_len(), 0);
+    assert!(!s.undo());
+    assert_eq!(s.styles().expect("styles").count(), 2);
+}
+
+#[test]
+fn test_focus_click_moves_caret() {
+    let mut s = TextInputState::new();
+    s.set_text("0123456789");
+    render(&mut s, 20);
+    s.set_selection(1, 3);
+
+    s.focus.set_gained(true);
+    let r = handle_events(&mut s, true, &click(2, KeyModifiers::NONE));
+    assert_eq!(r, TextOutcome::Unchanged);
+    assert_eq!(s.selection(), 1..3);
+
+    s.set_focus_click_moves_caret(true);
+    let r = handle_events(&mut s, true, &click(6, KeyModifiers::NONE));
+    assert_eq!(r, TextOutcome::Changed);
+    assert!(!s.has_selection());
+    assert_eq!(s.cursor(), 6);
+}
+
+#[test]
+fn test_scroll_step() {
+    let mut s = TextInputState::new();
+    s.set_text("0123456789");
+    render(&mut s, 5);
+    assert_eq!(s.scroll_step(), 1);
+    s.set_scroll_step(4);
+
+    let r = handle_events(&mut s, true, &key(KeyCode::Right, KeyModifiers::ALT));
+    assert_eq!(r, TextOutcome::Changed);
+    assert_eq!(s.offset(), 4);
+    handle_events(&mut s, true, &mouse(MouseEventKind::ScrollRight, 2));
+    assert_eq!(s.offset(), 8);
+
+    // clamped to the text
+    handle_events(&mut s, true, &key(KeyCode::Right, KeyModifiers::ALT));
+    assert_eq!(s.offset(), 10);
+    let r = handle_events(&mut s, true, &key(KeyCode::Right, KeyModifiers::ALT));
+    assert_eq!(r, TextOutcome::Unchanged);
+
+    handle_events(&mut s, true, &mouse(MouseEventKind::ScrollLeft, 2));
+    assert_eq!(s.offset(), 6);
+    handle_events(&mut s, true, &key(KeyCode::Left, KeyModifiers::ALT));
+    handle_events(&mut s, true, &key(KeyCode::Left, KeyModifiers::ALT));
+    assert_eq!(s.offset(), 0);
+}
+
+#[test]
+fn test_enter_inserts_space() {
+    let mut s = TextInputState::new();
+    s.set_text("ab");
+    s.set_cursor(1, false);
+
+    let r = handle_events(&mut s, true, &key(KeyCode::Enter, KeyModifiers::NONE));
+    assert_eq!(r, TextOutcome::Continue);
+    assert!(!s.insert_char('\n'));
+    assert_eq!(s.text(), "ab");
+
+    s.set_enter_inserts_space(true);
+    let r = handle_events(&mut s, true, &key(KeyCode::Enter, KeyModifiers::NONE));
+    assert_eq!(r, TextOutcome::TextChanged);
+    assert!(s.insert_char('\n'));
+    assert_eq!(s.text(), "a  b");
+}
+
+#[test]
+fn test_glyphs_with() {
+    let mut s = TextInputState::new();
+    s.set_expand_tabs(false);
+    s.insert_str("a\tb");
+    assert_eq!(s.text(), "a\tb");
+
+    let g = s.glyphs(0, 20).collect::<Vec<_>>();
+    assert_eq!(g[2].screen_pos().0, 8);
+
+    let g = s.glyphs_with(0, 20, 4, false).collect::<Vec<_>>();
+    assert_eq!(g[1].screen_width(), 3);
+    assert_eq!(g[2].screen_pos().0, 4);
+}
+
+#[test]
+fn test_hit() {
+    let mut s = TextInputState::new();
+    s.set_text("a日bcd");
+    s.set_cursor(4, false);
+
+    let area = Rect::new(0, 0, 12, 3);
+    let mut buf = Buffer::empty(area);
+    TextInput::new()
+        .block(Block::bordered())
+        .render(area, &mut buf, &mut s);
+
+    assert_eq!(s.hit(0, 0), Hit::Border);
+    assert_eq!(s.hit(11, 1), Hit::Border);
+    assert_eq!(s.hit(12, 1), Hit::Outside);
+    assert_eq!(s.hit(1, 1), Hit::Text(0));
+    assert_eq!(s.hit(2, 1), Hit::Text(1));
+    assert_eq!(s.hit(3, 1), Hit::Text(1));
+    assert_eq!(s.hit(4, 1), Hit::Text(2));
+    assert_eq!(s.hit(10, 1), Hit::Text(5));
+
+    // clicking the border doesn't move the caret
+    let r = handle_events(
+        &mut s,
+        true,
+        &mouse(MouseEventKind::Down(MouseButton::Left), 0),
+    );
+    assert_eq!(r, TextOutcome::Continue);
+    assert_eq!(s.cursor(), 4);
+}
+
+#[test]
+fn test_selection_markers() {
+    let mut s = TextInputState::new();
+    s.set_text("ab日cd");
+    s.set_selection(1, 3);
+
+    let area = Rect::new(0, 0, 10, 1);
+    let mut buf = Buffer::empty(area);
+    TextInput::new()
+        .selection_markers('[', ']')
+        .render(area, &mut buf, &mut s);
+    assert_eq!(buf[(0, 0)].symbol(), "a");
+    assert_eq!(buf[(1, 0)].symbol(), "[");
+    assert_eq!(buf[(2, 0)].symbol(), "日");
+    assert_eq!(buf[(3, 0)].symbol(), "]");
+    assert_eq!(buf[(4, 0)].symbol(), "c");
+
+    s.set_selection(3, 4);
+    let mut buf = Buffer::empty(area);
+    TextInput::new()
+        .selection_markers('[', ']')
+        .render(area, &mut buf, &mut s);
+    assert_eq!(buf[(2, 0)].symbol(), "日");
+    assert_eq!(buf[(4, 0)].symbol(), "]");
+    assert_eq!(buf[(5, 0)].symbol(), "d");
+}
+
+#[test]
+fn test_positions() {
+    let mut s = TextInputState::new();
+    s.set_text("a日b");
+    render(&mut s, 10);
+
+    let p = s.positions().collect::<Vec<_>>();
+    assert_eq!(
+        p.iter()
+            .map(|g| (g.pos, g.screen_pos, g.screen_width))
+            .collect::<Vec<_>>(),
+        vec![(0, 0, 1), (1, 1, 2), (2, 3, 1)]
+    );
+    assert_eq!(p[1].text_bytes, 1..4);
+    assert_eq!(s.glyph_at(2), Some(p[2].clone()));
+}
+
+#[test]
+fn test_guide_column() {
+    let mut s = TextInputState::new();
+    s.set_text("0123456789");
+    s.set_selection(9, 10);
+
+    let area = Rect::new(0, 0, 20, 1);
+    let mut buf = Buffer::empty(area);
+    TextInput::new()
+        .guide_column(8)
+        .guide_style(Style::new().bg(Color::Gray))
+        .inactive_select_style(Style::new().bg(Color::Yellow))
+        .render(area, &mut buf, &mut s);
+    assert_eq!(buf[(7, 0)].bg, Color::Reset);
+    assert_eq!(buf[(8, 0)].symbol(), "8");
+    assert_eq!(buf[(8, 0)].bg, Color::Gray);
+    // selection wins
+    assert_eq!(buf[(9, 0)].bg, Color::Yellow);
+    assert_eq!(buf[(10, 0)].bg, Color::Gray);
+    assert_eq!(buf[(19, 0)].bg, Color::Gray);
+
+    // guide after the end of the text
+    let mut buf = Buffer::empty(area);
+    TextInput::new()
+        .guide_column(12)
+        .guide_style(Style::new().bg(Color::Gray))
+        .render(area, &mut buf, &mut s);
+    assert_eq!(buf[(8, 0)].bg, Color::Reset);
+    assert_eq!(buf[(11, 0)].bg, Color::Reset);
+    assert_eq!(buf[(12, 0)].bg, Color::Gray);
+    assert_eq!(buf[(19, 0)].bg, Color::Gray);
+}
+
+#[test]
+fn test_word_at_cursor() {
+    let mut s = TextInputState::new();
+    s.set_text("alpha  beta");
+
+    s.set_cursor(0, false);
+    assert_eq!(s.word_at_cursor(), Some((0..5, "alpha")));
+    s.set_cursor(2, false);
+    assert_eq!(s.word_at_cursor(), Some((0..5, "alpha")));
+    s.set_cursor(5, false);
+    assert_eq!(s.word_at_cursor(), Some((0..5, "alpha")));
+    s.set_cursor(6, false);
+    assert_eq!(s.word_at_cursor(), None);
+    s.set_cursor(7, false);
+    assert_eq!(s.word_at_cursor(), Some((7..11, "beta")));
+    s.set_cursor(11, false);
+    assert_eq!(s.word_at_cursor(), Some((7..11, "beta")));
+
+    s.set_text("");
+    assert_eq!(s.word_at_cursor(), None);
+}
+
+#[test]
+fn test_underline_ranges() {
+    let mut s = TextInputState::new();
+    s.set_text("helo wrld ok");
+    s.add_range_style(10..12, 0).expect("valid");
+
+    s.set_underline_ranges(vec![0..4, 5..9], 1).expect("valid");
+    assert_eq!(
+        s.styles().expect("styles").collect::<Vec<_>>(),
+        vec![(0..4, 1), (5..9, 1), (10..12, 0)]
+    );
+
+    s.set_underline_ranges(vec![5..9], 1).expect("valid");
+    assert_eq!(
+        s.styles().expect("styles").collect::<Vec<_>>(),
+        vec![(5..9, 1), (10..12, 0)]
+    );
+
+    let area = Rect::new(0, 0, 20, 1);
+    let mut buf = Buffer::empty(area);
+    TextInput::new()
+        .text_style([
+            Style::new(),
+            Style::new().add_modifier(Modifier::UNDERLINED),
+        ])
+        .render(area, &mut buf, &mut s);
+    assert!(!buf[(0, 0)].modifier.contains(Modifier::UNDERLINED));
+    assert!(buf[(5, 0)].modifier.contains(Modifier::UNDERLINED));
+
+    s.clear_underline_ranges();
+    assert_eq!(
+        s.styles().expect("styles").collect::<Vec<_>>(),
+        vec![(10..12, 0)]
+    );
+}
+
+#[test]
+fn test_reset() {
+    let mut s = TextInputState::named("field");
+    s.set_text("hello world");
+    s.insert_str(" again");
+    s.set_selection(0, 5);
+    s.set_invalid(true);
+    s.add_style(0..5, 1);
+    s.set_underline_ranges(vec![6..11], 2).unwrap();
+    s.focus.set(true);
+
+    s.reset();
+
+    assert_eq!(s.text(), "");
+    assert_eq!(s.cursor(), 0);
+    assert!(!s.has_selection());
+    assert_eq!(s.offset(), 0);
+    assert!(!s.get_invalid());
+    assert_eq!(s.styles().map(|v| v.count()).unwrap_or_default(), 0);
+    assert_eq!(s.underline_style, None);
+    assert_eq!(s.undo_len(), 0);
+    assert!(!s.is_dirty());
+    assert_eq!(s.focus.name(), "field");
+    assert!(s.focus.get());
+}
+
+#[test]
+fn test_cursor_at_word_boundary() {
+    let mut s = TextInputState::new();
+    s.set_text("ab cd");
+
+    s.set_cursor(1, false);
+    assert!(!s.cursor_at_word_boundary());
+    s.set_cursor(2, false);
+    assert!(s.cursor_at_word_boundary());
+    s.set_cursor(3, false);
+    assert!(s.cursor_at_word_boundary());
+}
+
+#[test]
+fn test_insert_styled() {
+    let mut s = TextInputState::new();
+    s.set_text("ab");
+    s.set_cursor(1, false);
+
+    assert!(s.insert_styled("xyz", &[(1..2, 7), (2..10, 8)]));
+    assert_eq!(s.text(), "axyzb");
+    assert_eq!(s.cursor(), 4);
+
+    let mut styles = s.styles().expect("styles").collect::<Vec<_>>();
+    styles.sort();
+    assert_eq!(styles, vec![(2..3, 7), (3..4, 8)]);
+
+    // one undo step
+    s.undo();
+    assert_eq!(s.text(), "ab");
+}
+
+#[test]
+fn test_ring_clipboard() {
+    let clip = RingClipboard::new(2);
+    clip.set_string("a").expect("set");
+    clip.set_string("b").expect("set");
+    clip.set_string("c").expect("set");
+    assert_eq!(clip.len(), 2);
+    assert_eq!(clip.get_string().expect("get"), "c");
+    clip.cycle().expect("cycle");
+    assert_eq!(clip.get_string().expect("get"), "b");
+    clip.cycle().expect("cycle");
+    assert_eq!(clip.get_string().expect("get"), "c");
+}
+
+#[test]
+fn test_paste_cycle() {
+    let mut s = TextInputState::new();
+    let clip = RingClipboard::new(3);
+    clip.set_string("one").expect("set");
+    clip.set_string("two").expect("set");
+    s.set_clipboard(Some(clip));
+    s.set_text("x ");
+    s.set_cursor(2, false);
+
+    s.paste_from_clip();
+    assert_eq!(s.text(), "x two");
+    assert!(s.paste_cycle());
+    assert_eq!(s.text(), "x one");
+    assert!(s.paste_cycle());
+    assert_eq!(s.text(), "x two");
+
+    s.undo();
+    assert_eq!(s.text(), "x one");
+}
+
+#[test]
+fn test_batch_edit() {
+    let mut s = TextInputState::new();
+    s.set_text("hello world");
+    s.set_cursor(11, false);
+
+    s.batch_edit(|s| {
+        s.set_cursor(0, false);
+        s.insert_str("say ");
+        s.set_cursor(9, false);
+        s.insert_char(',');
+        s.delete_range(15..16);
+    });
+    assert_eq!(s.text(), "say hello, worl");
+
+    assert!(s.undo());
+    assert_eq!(s.text(), "hello world");
+    assert_eq!(s.cursor(), 11);
+    s.redo();
+    assert_eq!(s.text(), "say hello, worl");
+}
+
+#[test]
+fn test_batch_edit_grapheme() {
+    let mut s = TextInputState::new();
+    s.set_text("ae\u{301}b");
+    s.undo_buffer_mut().expect("undo").enable_replay_log(true);
+    let mut s2 = s.clone();
+
+    // only the combining mark differs.
+    s.batch_edit(|s| {
+        s.delete_range(1..2);
+        s.set_cursor(1, false);
+        s.insert_str("e\u{300}");
+    });
+    assert_eq!(s.text(), "ae\u{300}b");
+
+    // the recorded change covers the whole grapheme.
+    let replay = s.recent_replay_log();
+    let txt = replay
+        .iter()
+        .filter_map(|v| match &v.operation {
+            UndoOp::RemoveStr { txt, .. } | UndoOp::InsertStr { txt, .. } => Some(txt.as_str()),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(txt, vec!["e\u{301}", "e\u{300}"]);
+
+    s2.replay_log(&replay);
+    assert_eq!(s2.text(), "ae\u{300}b");
+
+    assert!(s.undo());
+    assert_eq!(s.text(), "ae\u{301}b");
+}
+
+#[test]
+fn test_insert_remove_at() {
+    let mut s = TextInputState::new();
+    s.set_text("abcd");
+    s.set_cursor(2, false);
+
+    // after the cursor
+    assert!(s.insert_char_at(3, 'x'));
+    assert_eq!(s.text(), "abcxd");
+    assert_eq!(s.cursor(), 2);
+    // at the cursor
+    assert!(s.insert_char_at(2, 'y'));
+    assert_eq!(s.text(), "abycxd");
+    assert_eq!(s.cursor(), 3);
+    // before the cursor
+    assert!(s.insert_char_at(0, 'z'));
+    assert_eq!(s.text(), "zabycxd");
+    assert_eq!(s.cursor(), 4);
+
+    assert!(s.remove_grapheme_at(5));
+    assert_eq!(s.text(), "zabycd");
+    assert_eq!(s.cursor(), 4);
+    assert!(s.remove_grapheme_at(4));
+    assert_eq!(s.text(), "zabyd");
+    assert_eq!(s.cursor(), 4);
+    assert!(s.remove_grapheme_at(0));
+    assert_eq!(s.text(), "abyd");
+    assert_eq!(s.cursor(), 3);
+    assert!(!s.remove_grapheme_at(4));
+
+    assert_eq!(
+        s.try_insert_char_at(0, '\n'),
+        Err(TextError::InvalidText("\n".into()))
+    );
+    assert!(s.try_insert_char_at(9, 'a').is_err());
+
+    s.undo();
+    assert_eq!(s.text(), "zabyd");
+}
+
+#[test]
+fn test_insert_char_at_limits() {
+    let rejected = Rc::new(Cell::new(0));
+    let mut s = TextInputState::new();
+    s.set_text("ab");
+    s.set_max_bytes(Some(3));
+    s.set_on_reject(Some(Rc::new({
+        let rejected = rejected.clone();
+        move || rejected.set(rejected.get() + 1)
+    })));
+
+    assert!(!s.insert_char_at(1, '日'));
+    assert_eq!(s.text(), "ab");
+    assert!(s.insert_char_at(1, 'x'));
+    assert_eq!(s.text(), "axb");
+    assert!(!s.insert_char_at(0, 'y'));
+    assert_eq!(s.text(), "axb");
+    assert_eq!(rejected.get(), 2);
+
+    s.set_max_bytes(None);
+    s.set_collapse_spaces(true);
+    s.set_char_remap(Some(|c| if c == '_' { Some(' ') } else { Some(c) }));
+    assert!(s.insert_char_at(1, '_'));
+    assert_eq!(s.text(), "a xb");
+    assert!(!s.insert_char_at(2, ' '));
+    assert_eq!(s.text(), "a xb");
+}
+
+fn render_placeholder(state: &mut TextInputState, when: PlaceholderWhen) -> Buffer {
+    let area = Rect::new(0, 0, 12, 1);
+    let mut buf = Buffer::empty(area);
+    TextInput::new()
+        .placeholder("name")
+        .placeholder_when(when)
+        .render(area, &mut buf, state);
+    buf
+}
+
+fn row(buf: &Buffer) -> String {
+    (0..buf.area.width)
+        .map(|x| buf[(x, 0)].symbol())
+        .collect::<String>()
+}
+
+#[test]
+fn test_placeholder_empty() {
+    let mut s = TextInputState::new();
+    assert_eq!(
+        row(&render_placeholder(&mut s, PlaceholderWhen::Empty)),
+        "name        "
+    );
+    s.focus.set(true);
+    assert_eq!(
+        row(&render_placeholder(&mut s, PlaceholderWhen::Empty)),
+        "name        "
+    );
+    s.set_text("ab");
+    assert_eq!(
+        row(&render_placeholder(&mut s, PlaceholderWhen::Empty)),
+        "ab          "
+    );
+}
+
+#[test]
+fn test_placeholder_unfocused() {
+    let mut s = TextInputState::new();
+    assert_eq!(
+        row(&render_placeholder(&mut s, PlaceholderWhen::Unfocused)),
+        "name        "
+    );
+    s.focus.set(true);
+    assert_eq!(
+        row(&render_placeholder(&mut s, PlaceholderWhen::Unfocused)),
+        "            "
+    );
+    s.focus.set(false);
+    s.set_text("ab");
+    assert_eq!(
+        row(&render_placeholder(&mut s, PlaceholderWhen::Unfocused)),
+        "ab          "
+    );
+}
+
+#[test]
+fn test_placeholder_always() {
+    let mut s = TextInputState::new();
+    assert_eq!(
+        row(&render_placeholder(&mut s, PlaceholderWhen::Always)),
+        "name        "
+    );
+    s.set_text("ab");
+    let buf = render_placeholder(&mut s, PlaceholderWhen::Always);
+    assert_eq!(row(&buf), "ab      name");
+    assert!(buf[(8, 0)].modifier.contains(Modifier::DIM));
+    assert!(!buf[(1, 0)].modifier.contains(Modifier::DIM));
+    s.set_text("abcdefg");
+    assert_eq!(
+        row(&render_placeholder(&mut s, PlaceholderWhen::Always)),
+        "abcdefg name"
+    );
+    // no room left.
+    s.set_text("abcdefgh");
+    assert_eq!(
+        row(&render_placeholder(&mut s, PlaceholderWhen::Always)),
+        "abcdefgh    "
+    );
+}
+
+#[test]
+fn test_widget_inner() {
+    let area = Rect::new(2, 3, 12, 3);
+    let w = TextInput::new().block(Block::bordered());
+    assert_eq!(w.inner(area), Rect::new(3, 4, 10, 1));
+    assert_eq!(TextInput::new().inner(area), area);
+
+    let mut s = TextInputState::new();
+    let mut buf = Buffer::empty(Rect::new(0, 0, 20, 10));
+    let inner = w.inner(area);
+    w.render(area, &mut buf, &mut s);
+    assert_eq!(s.inner, inner);
+}
+
+#[test]
+fn test_text_around_cursor() {
+    let mut s = TextInputState::new();
+    s.set_text("añ日e\u{301}x");
+
+    s.set_cursor(3, false);
+    assert_eq!(s.text_before_cursor(), "añ日");
+    assert_eq!(s.text_after_cursor(), "e\u{301}x");
+
+    s.set_cursor(4, false);
+    assert_eq!(s.text_before_cursor(), "añ日e\u{301}");
+    assert_eq!(s.text_after_cursor(), "x");
+
+    s.set_cursor(0, false);
+    assert_eq!(s.text_before_cursor(), "");
+    assert_eq!(s.text_after_cursor(), s.text());
+}
+
+#[test]
+fn test_handle_events_debug() {
+    let mut s = TextInputState::new();
+    s.set_text("ab");
+
+    let ev = Event::Key(KeyEvent::new(KeyCode::Char('x'), KeyModifiers::NONE));
+    assert_eq!(
+        handle_events_debug(&mut s, true, &ev),
+        (TextOutcome::TextChanged, Some("insert_char"))
+    );
+    let ev = Event::Key(KeyEvent::new(KeyCode::Left, KeyModifiers::NONE));
+    assert_eq!(
+        handle_events_debug(&mut s, true, &ev),
+        (TextOutcome::Changed, Some("move_left"))
+    );
+    let ev = Event::Key(KeyEvent::new(KeyCode::Char('z'), KeyModifiers::CONTROL));
+    assert_eq!(handle_events_debug(&mut s, true, &ev).1, Some("undo"));
+    let ev = Event::Key(KeyEvent::new(KeyCode::F(1), KeyModifiers::NONE));
+    assert_eq!(
+        handle_events_debug(&mut s, true, &ev),
+        (TextOutcome::Continue, None)
+    );
+    assert_eq!(
+        handle_events_debug(&mut s, true, &Event::Paste("x".into())),
+        (TextOutcome::Continue, None)
+    );
+
+    render(&mut s, 10);
+    assert_eq!(
+        handle_events_debug(&mut s, true, &click(1, KeyModifiers::NONE)),
+        (TextOutcome::Changed, Some("set_cursor"))
+    );
+
+    // the tab that brought the focus.
+    s.focus.set_gained(true);
+    let ev = Event::Key(KeyEvent::new(KeyCode::Tab, KeyModifiers::NONE));
+    assert_eq!(
+        handle_events_debug(&mut s, true, &ev),
+        (TextOutcome::Unchanged, Some("ignore_tab"))
+    );
+    s.focus.set_gained(false);
+
+    // the change comes from trimming, not from the key.
+    s.set_text(" ab ");
+    s.set_trim_on_blur(true);
+    s.focus.set_lost(true);
+    let ev = Event::Key(KeyEvent::new(KeyCode::F(1), KeyModifiers::NONE));
+    assert_eq!(
+        handle_events_debug(&mut s, false, &ev),
+        (TextOutcome::TextChanged, Some("trim"))
+    );
+}
+
+#[test]
+fn test_collapse_spaces() {
+    let mut s = TextInputState::new();
+    s.set_collapse_spaces(true);
+
+    let r = s.insert_str_result("a   b");
+    assert_eq!(s.text(), "a b");
+    assert_eq!(r.rejected, 2);
+
+    assert!(s.insert_char(' '));
+    assert!(!s.insert_char(' '));
+    assert_eq!(s.text(), "a b ");
+    s.insert_str("  c");
+    assert_eq!(s.text(), "a b c");
+
+    // a single space is still fine.
+    s.set_cursor(2, false);
+    assert!(!s.insert_char(' '));
+    s.set_cursor(0, false);
+    assert!(s.insert_char(' '));
+    assert_eq!(s.text(), " a b c");
+}
+
+#[test]
+fn test_value_store() {
+    let mut s = TextInputState::new();
+    s.set_text("hello");
+    s.set_cursor(5, false);
+    s.add_style(0..5, 1);
+    assert_eq!(s.value_store().as_str(), "hello");
+
+    let n = s.with_value_store_mut(|v| {
+        *v = TextString::new_text("hi\nyo");
+        v.as_str().len()
+    });
+    assert_eq!(n, 5);
+    assert_eq!(s.text(), "hi yo");
+    assert_eq!(s.cursor(), 5);
+    assert_eq!(s.styles().map(|v| v.count()).unwrap_or_default(), 0);
+
+    s.with_value_store_mut(|v| *v = TextString::new_text("x"));
+    assert_eq!(s.cursor(), 1);
+}
+
+#[test]
+fn test_select_trimmed() {
+    let mut s = TextInputState::new();
+    s.set_text("  ab cd\t ");
+
+    assert!(s.select_trimmed());
+    assert_eq!(s.selection(), 2..7);
+    assert_eq!(s.selected_text(), "ab cd");
+    assert!(!s.select_trimmed());
+
+    assert!(s.select_line());
+    assert_eq!(s.selection(), 0..9);
+
+    s.set_text("   ");
+    s.select_all();
+    assert!(s.select_trimmed());
+    assert!(!s.has_selection());
+    assert_eq!(s.cursor(), 0);
+}
+
+#[test]
+fn test_delete_prev_word_at_start() {
+    let mut s = TextInputState::new();
+    s.set_text("ab cd");
+    s.set_cursor(0, false);
+
+    let r = handle_events(
+        &mut s,
+        true,
+        &key(KeyCode::Backspace, KeyModifiers::CONTROL),
+    );
+    assert_eq!(r, TextOutcome::Unchanged);
+    assert_eq!(s.text(), "ab cd");
+    assert_eq!(s.cursor(), 0);
+    assert!(!s.has_selection());
+
+    // only whitespace before the cursor.
+    s.set_text("   cd");
+    s.set_cursor(3, false);
+    let r = handle_events(
+        &mut s,
+        true,
+        &key(KeyCode::Backspace, KeyModifiers::CONTROL),
+    );
+    assert_eq!(r, TextOutcome::TextChanged);
+    assert_eq!(s.text(), "cd");
+    assert_eq!(s.cursor(), 0);
+
+    // the whitespace run is removed, the word before stays.
+    s.set_text("ab   cd");
+    s.set_cursor(5, false);
+    assert!(s.delete_prev_word());
+    assert_eq!(s.text(), "abcd");
+    assert_eq!(s.cursor(), 2);
+}
+
+#[test]
+fn test_set_view() {
+    let mut s = TextInputState::new();
+    s.set_text("0123456789abcdefghij");
+    render(&mut s, 5);
+
+    // cursor visible, the offset is kept as is.
+    assert!(s.set_view(8, 6));
+    assert_eq!(s.cursor(), 8);
+    assert_eq!(s.offset(), 6);
+    assert!(!s.set_view(8, 6));
+
+    // cursor outside, the view follows.
+    assert!(s.set_view(15, 2));
+    assert_eq!(s.cursor(), 15);
+    assert_eq!(s.offset(), 11);
+
+    // before render nothing is corrected.
+    let mut s = TextInputState::new();
+    s.set_text("0123456789");
+    s.set_view(1, 7);
+    assert_eq!(s.offset(), 7);
+}
+
+#[test]
+fn test_find_rfind() {
+    let mut s = TextInputState::new();
+    s.set_text("ab日 AB日 ab");
+
+    assert_eq!(s.find("ab", 0), Some(0..2));
+    assert_eq!(s.find("ab", 1), Some(8..10));
+    assert_eq!(s.find("b日", 0), Some(1..3));
+    assert_eq!(s.find("ab", 9), None);
+    assert_eq!(s.find("", 0), None);
+
+    assert_eq!(s.rfind("ab", 10), Some(8..10));
+    assert_eq!(s.rfind("ab", 9), Some(0..2));
+    assert_eq!(s.rfind("ab", 1), None);
+
+    assert_eq!(s.find_ignore_case("ab", 1), Some(4..6));
+    assert_eq!(s.rfind_ignore_case("ab", 9), Some(4..6));
+
+    // only whole graphemes match.
+    s.set_text("e\u{301}e");
+    assert_eq!(s.find("e", 0), Some(1..2));
+    assert_eq!(s.rfind("e", 2), Some(1..2));
+}
+
+#[test]
+fn test_invalid_reason() {
+    let mut s = TextInputState::new();
+    assert_eq!(s.invalid_reason(), None);
+
+    s.set_invalid_reason(Some("not a number".into()));
+    assert!(s.get_invalid());
+    assert_eq!(s.invalid_reason(), Some("not a number"));
+
+    // still invalid, the reason stays.
+    s.set_invalid(true);
+    assert_eq!(s.invalid_reason(), Some("not a number"));
+
+    s.set_invalid(false);
+    assert!(!s.get_invalid());
+    assert_eq!(s.invalid_reason(), None);
+
+    s.set_invalid_reason(Some("too long".into()));
+    s.set_invalid_reason(None);
+    assert!(!s.get_invalid());
+}
+
+#[test]
+fn test_overflow_indicators() {
+    let mut s = TextInputState::new();
+    s.set_text("0123456789");
+
+    let area = Rect::new(0, 0, 5, 1);
+    let mut buf = Buffer::empty(area);
+    TextInput::new()
+        .overflow_indicators(true)
+        .overflow_style(Style::new().fg(Color::Red))
+        .render(area, &mut buf, &mut s);
+    assert_eq!(buf[(0, 0)].symbol(), "0");
+    assert_eq!(buf[(4, 0)].symbol(), "›");
+    assert_eq!(buf[(4, 0)].fg, Color::Red);
+    assert_eq!(buf[(3, 0)].fg, Color::Reset);
+
+    s.set_offset(3);
+    let mut buf = Buffer::empty(area);
+    TextInput::new()
+        .overflow_indicators(true)
+        .render(area, &mut buf, &mut s);
+    assert_eq!(buf[(0, 0)].symbol(), "‹");
+    assert!(buf[(0, 0)].modifier.contains(Modifier::DIM));
+    assert!(!buf[(1, 0)].modifier.contains(Modifier::DIM));
+    assert_eq!(buf[(4, 0)].symbol(), "›");
+
+    s.set_offset(6);
+    let mut buf = Buffer::empty(area);
+    TextInput::new()
+        .overflow_indicators(true)
+        .render(area, &mut buf, &mut s);
+    assert_eq!(buf[(0, 0)].symbol(), "‹");
+    assert_eq!(buf[(3, 0)].symbol(), "9");
+    assert_eq!(buf[(4, 0)].symbol(), " ");
+}
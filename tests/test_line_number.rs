@@ -0,0 +1,189 @@
+use crossterm::event::{Event, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
+use rat_text::line_number::{LineNumberState, LineNumbers};
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::text::Line;
+use ratatui::widgets::StatefulWidget;
+
+#[test]
+fn test_flag_columns() {
+    let ln = LineNumbers::new()
+        .end(10)
+        .flags(vec![Line::from("*"), Line::from("")])
+        .flag_column(2, vec![Line::from(""), Line::from("BP")]);
+    assert_eq!(ln.width(), 7);
+
+    let area = Rect::new(0, 0, 7, 2);
+    let mut buf = Buffer::empty(area);
+    let mut state = LineNumberState::new();
+    ln.render(area, &mut buf, &mut state);
+
+    assert_eq!(buf[(0, 0)].symbol(), " ");
+    assert_eq!(buf[(1, 0)].symbol(), "0");
+    assert_eq!(buf[(3, 0)].symbol(), "*");
+    assert_eq!(buf[(1, 1)].symbol(), "1");
+    assert_eq!(buf[(5, 1)].symbol(), "B");
+    assert_eq!(buf[(6, 1)].symbol(), "P");
+}
+
+fn mouse(kind: MouseEventKind, row: u16) -> Event {
+    Event::Mouse(MouseEvent {
+        kind,
+        column: 1,
+        row,
+        modifiers: KeyModifiers::NONE,
+    })
+}
+
+#[test]
+fn test_dragged_range() {
+    let area = Rect::new(0, 0, 4, 5);
+    let mut buf = Buffer::empty(area);
+    let mut state = LineNumberState::new();
+    LineNumbers::new()
+        .start(10)
+        .render(area, &mut buf, &mut state);
+
+    let r = state.dragged_range(&mouse(MouseEventKind::Down(MouseButton::Left), 2));
+    assert_eq!(r, Some(12..13));
+    let r = state.dragged_range(&mouse(MouseEventKind::Drag(MouseButton::Left), 3));
+    assert_eq!(r, Some(12..14));
+    let r = state.dragged_range(&mouse(MouseEventKind::Drag(MouseButton::Left), 0));
+    assert_eq!(r, Some(10..13));
+    // clamped
+    let r = state.dragged_range(&mouse(MouseEventKind::Drag(MouseButton::Left), 20));
+    assert_eq!(r, Some(12..15));
+    let r = state.dragged_range(&mouse(MouseEventKind::Up(MouseButton::Left), 20));
+    assert_eq!(r, None);
+}
+
+#[test]
+fn test_width_power_of_ten() {
+    for (start, nr_width) in [(895, 3), (899, 3), (900, 4), (901, 4), (999, 4)] {
+        let ln = LineNumbers::new().start(start);
+        let width = ln.width();
+        assert_eq!(width, nr_width + 1);
+
+        let area = Rect::new(0, 0, width, 10);
+        let mut buf = Buffer::empty(area);
+        let mut state = LineNumberState::new();
+        ln.render(area, &mut buf, &mut state);
+
+        // every number fits and is right aligned to the same column.
+        for y in 0..10 {
+            let row = (0..nr_width)
+                .map(|x| buf[(x, y)].symbol())
+                .collect::<String>();
+            assert_eq!(row.trim_start(), (start + y as u32).to_string());
+        }
+    }
+}
+
+fn nr(buf: &Buffer, y: u16) -> String {
+    (0..3).map(|x| buf[(x, y)].symbol()).collect::<String>()
+}
+
+#[test]
+fn test_width_for_rows() {
+    let mut state = LineNumberState::new();
+    let ln = LineNumbers::new().start(850);
+    // 850 + 100 rows
+    assert_eq!(ln.width(), 4);
+    assert_eq!(ln.width_for(&state, 100), 4);
+    // 850 + 200 rows
+    assert_eq!(ln.width_for(&state, 200), 5);
+
+    let area = Rect::new(0, 0, ln.width_for(&state, 200), 200);
+    let mut buf = Buffer::empty(area);
+    ln.render(area, &mut buf, &mut state);
+    let row = (0..4).map(|x| buf[(x, 199)].symbol()).collect::<String>();
+    assert_eq!(row, "1049");
+}
+
+#[test]
+fn test_width_for_offset() {
+    let mut state = LineNumberState::new();
+    state.set_offset(950);
+
+    let ln = LineNumbers::new();
+    let width = ln.width_for(&state, 60);
+    assert_eq!(width, 5);
+
+    let area = Rect::new(0, 0, width, 60);
+    let mut buf = Buffer::empty(area);
+    ln.render(area, &mut buf, &mut state);
+
+    // 4 digits and the separator fill the gutter.
+    let row = (0..width).map(|x| buf[(x, 0)].symbol()).collect::<String>();
+    assert_eq!(row, " 950 ");
+    let row = (0..width)
+        .map(|x| buf[(x, 59)].symbol())
+        .collect::<String>();
+    assert_eq!(row, "1009 ");
+}
+
+#[test]
+fn test_state_offset() {
+    let area = Rect::new(0, 0, 4, 2);
+    let mut state = LineNumberState::new();
+    state.set_offset(20);
+
+    let mut buf = Buffer::empty(area);
+    LineNumbers::new().render(area, &mut buf, &mut state);
+    assert_eq!(state.start, 20);
+    assert_eq!(nr(&buf, 0), " 20");
+    assert_eq!(nr(&buf, 1), " 21");
+
+    // the builder overrides the state.
+    let mut buf = Buffer::empty(area);
+    LineNumbers::new()
+        .start(30)
+        .render(area, &mut buf, &mut state);
+    assert_eq!(state.start, 30);
+    assert_eq!(nr(&buf, 0), " 30");
+    assert_eq!(state.offset(), 20);
+}
+
+#[test]
+fn test_width_for_state() {
+    let mut state = LineNumberState::new();
+    state.set_offset(950);
+
+    let ln = LineNumbers::new();
+    // 950 + 100
+    assert_eq!(ln.width_for(&state, 10), 5);
+    assert_eq!(ln.width(), 4);
+
+    // the builder wins.
+    let ln = LineNumbers::new().start(10);
+    assert_eq!(ln.width_for(&state, 10), 4);
+}
+
+#[test]
+fn test_row_lines() {
+    let area = Rect::new(0, 0, 4, 5);
+    let mut state = LineNumberState::new();
+
+    let mut buf = Buffer::empty(area);
+    LineNumbers::new()
+        .row_lines(vec![7, 8, 8, 9])
+        .render(area, &mut buf, &mut state);
+    assert_eq!(state.start, 7);
+    assert_eq!(nr(&buf, 0), "  7");
+    assert_eq!(nr(&buf, 1), "  8");
+    assert_eq!(nr(&buf, 2), "   ");
+    assert_eq!(nr(&buf, 3), "  9");
+    assert_eq!(nr(&buf, 4), "   ");
+
+    let r = state.dragged_range(&mouse(MouseEventKind::Down(MouseButton::Left), 2));
+    assert_eq!(r, Some(8..9));
+    let r = state.dragged_range(&mouse(MouseEventKind::Drag(MouseButton::Left), 3));
+    assert_eq!(r, Some(8..10));
+
+    let mut buf = Buffer::empty(area);
+    LineNumbers::new()
+        .row_lines(vec![7, 8, 8, 9])
+        .continuation_marker('↪')
+        .render(area, &mut buf, &mut state);
+    assert_eq!(nr(&buf, 2), "  ↪");
+}
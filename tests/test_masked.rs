@@ -465,3 +465,16 @@ fn test_section2() {
     m.insert_char('€');
     assert_eq!(m.cursor(), 9);
 }
+
+#[test]
+fn test_value_unmasked() {
+    let mut m = MaskedCore::new();
+    m.set_mask("\\(999\\) 999\\-9999").expect("ok");
+    m.set_text("(555) 12 -3456");
+    assert_eq!(m.value_unmasked(), "555123456");
+
+    let mut m = MaskedCore::new();
+    m.set_mask("ll").expect("ok");
+    m.set_text("a ");
+    assert_eq!(m.value_unmasked(), "a");
+}
@@ -0,0 +1,29 @@
+#![cfg(feature = "serde")]
+
+use rat_text::text_input::TextInputState;
+use rat_text::undo_buffer::{decode_replay, encode_replay};
+
+#[test]
+fn test_replay_roundtrip() {
+    let mut s = TextInputState::new();
+    s.undo_buffer_mut().expect("undo").enable_replay_log(true);
+    let mut s2 = s.clone();
+
+    s.insert_str("hello wörld");
+    s.set_cursor(5, false);
+    s.insert_char(',');
+    s.set_selection(7, 12);
+    s.insert_str("日本");
+    s.delete_prev_char();
+    s.add_style(0..5, 1);
+
+    let data = encode_replay(&s.recent_replay_log());
+    let replay = decode_replay(&data).expect("valid replay");
+    s2.replay_log(&replay);
+
+    assert_eq!(s2.text(), s.text());
+    assert_eq!(s2.text(), "hello, 日");
+    assert_eq!(s2.cursor(), s.cursor());
+
+    assert!(decode_replay(b"garbage").is_err());
+}